@@ -1,15 +1,46 @@
 use airplane_landing_scheduler::*;
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use metaheuristics::{simulated_annealing, Problem};
-use problem::LandingProblem;
+use criterion::{criterion_group, criterion_main, Criterion};
+use metaheuristics::{simulated_annealing, IncrementalProblem, NotIncremental, Problem};
+use problem::{LandingProblem, Solution};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Hides the incremental evaluation of the wrapped problem, so the
+/// metaheuristics build and fully evaluate every neighbor
+struct FullEvaluation<'a>(&'a LandingProblem);
+
+impl Problem<Solution> for FullEvaluation<'_> {
+  type Incremental = NotIncremental;
+
+  fn initial_solution(&self) -> Solution {
+    self.0.initial_solution()
+  }
+
+  fn random_neighbor<R: Rng + ?Sized>(&self, solution: &Solution, rng: &mut R) -> Solution {
+    self.0.random_neighbor(solution, rng)
+  }
+
+  fn first_improvement_neighbor<R: Rng + ?Sized>(
+    &self,
+    solution: &Solution,
+    rng: &mut R,
+  ) -> Solution {
+    self.0.first_improvement_neighbor(solution, rng)
+  }
+
+  fn cost(&self, solution: &Solution) -> f64 {
+    self.0.cost(solution)
+  }
+}
 
 fn criterion_benchmark(c: &mut Criterion) {
   let problem =
     LandingProblem::from_parser(parser::parse_problem_data("data/airland9.txt").unwrap());
   let s = problem.initial_solution();
+  let mut rng = StdRng::seed_from_u64(0);
 
   c.bench_function("random neighbor", |b| {
-    b.iter(|| problem.random_neighbor(&s))
+    b.iter(|| problem.random_neighbor(&s, &mut rng))
   });
 
   // c.bench_function("cost", |b| b.iter(|| problem.cost(&s)));
@@ -19,7 +50,28 @@ fn criterion_benchmark(c: &mut Criterion) {
   c.bench_function("landing cost", |b| b.iter(|| problem.landing_cost(&s)));
 
   c.bench_function("simulated annealing", |b| {
-    b.iter(|| simulated_annealing(&problem, &s, 1000, 0.99, 50, 1000.0))
+    b.iter(|| simulated_annealing(&problem, &s, 1000, 0.99, 50, 1000.0, &mut rng))
+  });
+
+  let problem =
+    LandingProblem::from_parser(parser::parse_problem_data("data/airland13.txt").unwrap());
+  let full = FullEvaluation(&problem);
+  let s = problem.initial_solution();
+
+  c.bench_function("airland13 full evaluation", |b| {
+    b.iter(|| problem.cost(&problem.random_neighbor(&s, &mut rng)) - problem.cost(&s))
+  });
+
+  c.bench_function("airland13 delta evaluation", |b| {
+    b.iter(|| problem.random_move(&s, &mut rng).unwrap().1)
+  });
+
+  c.bench_function("airland13 simulated annealing full evaluation", |b| {
+    b.iter(|| simulated_annealing(&full, &s, 1000, 0.99, 50, 1000.0, &mut rng))
+  });
+
+  c.bench_function("airland13 simulated annealing delta evaluation", |b| {
+    b.iter(|| simulated_annealing(&problem, &s, 1000, 0.99, 50, 1000.0, &mut rng))
   });
 }
 
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::{error, fmt};
+
+/// Best known landing cost of an instance on a number of runways
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BestKnown {
+  pub cost: f64,
+  /// Whether `cost` is proven optimal
+  pub optimal: bool,
+}
+
+/// OR-Library instances on 1 to 5 runways: (instance, costs by number of
+/// runways, number of them that are proven optimal). Instances 1 to 8 were
+/// solved to optimality by Beasley et al. (2000), the values of 9 to 13 are
+/// the best published ones. Past the runways listed, the cost is zero
+const BUILTIN: [(&str, &[f64], usize); 13] = [
+  ("airland1", &[700.0, 90.0, 0.0], 3),
+  ("airland2", &[1480.0, 210.0, 0.0], 3),
+  ("airland3", &[820.0, 60.0, 0.0], 3),
+  ("airland4", &[2520.0, 640.0, 130.0, 0.0], 4),
+  ("airland5", &[3100.0, 650.0, 170.0, 0.0], 4),
+  ("airland6", &[24442.0, 554.0, 0.0], 3),
+  ("airland7", &[1550.0, 0.0], 2),
+  ("airland8", &[1950.0, 135.0, 0.0], 3),
+  ("airland9", &[5611.70, 444.10, 75.75, 0.0], 0),
+  ("airland10", &[12292.20, 1143.69, 88.72, 0.0], 0),
+  ("airland11", &[12418.32, 1330.45, 84.71, 0.0], 0),
+  ("airland12", &[16209.78, 1946.92, 158.36, 0.0], 0),
+  ("airland13", &[37064.11, 3002.86, 405.69, 0.0], 0),
+];
+
+pub const MAX_RUNWAYS: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct BestKnownTable {
+  /// By instance name, without extension, and number of runways
+  entries: HashMap<(String, usize), BestKnown>,
+}
+
+impl BestKnownTable {
+  pub fn builtin() -> Self {
+    let mut entries = HashMap::new();
+    for (instance, costs, proven) in BUILTIN {
+      for runways in 1..=MAX_RUNWAYS {
+        let (cost, optimal) = match costs.get(runways - 1) {
+          Some(&cost) => (cost, runways <= proven || cost == 0.0),
+          None => (0.0, true),
+        };
+        entries.insert((instance.to_string(), runways), BestKnown { cost, optimal });
+      }
+    }
+    BestKnownTable { entries }
+  }
+
+  /// Best known cost of `instance`, a file name or path, on `runways`
+  pub fn get(&self, instance: &str, runways: usize) -> Option<BestKnown> {
+    self
+      .entries
+      .get(&(instance_name(instance), runways))
+      .copied()
+  }
+
+  pub fn set(&mut self, instance: &str, runways: usize, best: BestKnown) {
+    self
+      .entries
+      .insert((instance_name(instance), runways), best);
+  }
+
+  /// Replaces entries with the rows `instance,runways,cost[,optimal]` of a
+  /// CSV file. A first line that does not parse is taken as a header
+  pub fn read_overrides(&mut self, reader: impl BufRead) -> Result<(), OverrideError> {
+    for (k, line) in reader.lines().enumerate() {
+      let line = line?;
+      if line.trim().is_empty() {
+        continue;
+      }
+      match parse_row(&line) {
+        Some((instance, runways, best)) => self.set(instance, runways, best),
+        None if k == 0 => {}
+        None => {
+          return Err(OverrideError::Format {
+            line: k + 1,
+            row: line,
+          })
+        }
+      }
+    }
+    Ok(())
+  }
+
+  pub fn read_overrides_from(&mut self, file_path: &str) -> Result<(), OverrideError> {
+    self.read_overrides(io::BufReader::new(File::open(file_path)?))
+  }
+}
+
+fn parse_row(line: &str) -> Option<(&str, usize, BestKnown)> {
+  let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+  let (instance, runways, cost, optimal) = match fields[..] {
+    [instance, runways, cost] => (instance, runways, cost, "false"),
+    [instance, runways, cost, optimal] => (instance, runways, cost, optimal),
+    _ => return None,
+  };
+  let best = BestKnown {
+    cost: cost.parse().ok()?,
+    optimal: optimal.parse().ok()?,
+  };
+  Some((instance, runways.parse().ok()?, best))
+}
+
+/// File name of `instance` without directories and extension
+fn instance_name(instance: &str) -> String {
+  let name = instance.rsplit(['/', '\\']).next().unwrap();
+  name.split('.').next().unwrap().to_string()
+}
+
+/// Percentage by which `cost` exceeds `best`, negative when it beats it, and
+/// infinite if `best` is zero and `cost` is not
+pub fn gap(cost: f64, best: f64) -> f64 {
+  if best == 0.0 {
+    if cost == 0.0 {
+      0.0
+    } else {
+      f64::INFINITY
+    }
+  } else {
+    100.0 * (cost - best) / best
+  }
+}
+
+#[derive(Debug)]
+pub enum OverrideError {
+  Io(io::Error),
+  Format {
+    /// 1-based line of the offending row
+    line: usize,
+    row: String,
+  },
+}
+
+impl fmt::Display for OverrideError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      OverrideError::Io(e) => write!(f, "I/O error: {}", e),
+      OverrideError::Format { line, row } => write!(
+        f,
+        "line {}: expected instance,runways,cost[,optimal], found {:?}",
+        line, row
+      ),
+    }
+  }
+}
+
+impl error::Error for OverrideError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      OverrideError::Io(e) => Some(e),
+      OverrideError::Format { .. } => None,
+    }
+  }
+}
+
+impl From<io::Error> for OverrideError {
+  fn from(e: io::Error) -> Self {
+    OverrideError::Io(e)
+  }
+}
@@ -0,0 +1,342 @@
+use crate::metaheuristics::RandomizedConstruction;
+use crate::problem::{Arrival, LandingProblem, Runway, Solution, ID};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::{error, fmt};
+
+/// Deterministic constructive heuristics, landing the planes one at a time
+/// separated from every plane landed before
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Heuristic {
+  /// By target time, at the target time or the first separated time after it
+  FirstComeFirstServed,
+  /// By latest landing time, at the earliest separated time
+  EarliestDeadlineFirst,
+  /// The plane with the cheapest separated time first, at that time
+  LowestCostInsertion,
+}
+
+impl Heuristic {
+  pub const ALL: [Heuristic; 3] = [
+    Heuristic::FirstComeFirstServed,
+    Heuristic::EarliestDeadlineFirst,
+    Heuristic::LowestCostInsertion,
+  ];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Heuristic::FirstComeFirstServed => "fcfs",
+      Heuristic::EarliestDeadlineFirst => "edf",
+      Heuristic::LowestCostInsertion => "lci",
+    }
+  }
+}
+
+impl FromStr for Heuristic {
+  type Err = UnknownHeuristic;
+
+  fn from_str(name: &str) -> Result<Self, Self::Err> {
+    Heuristic::ALL
+      .into_iter()
+      .find(|heuristic| heuristic.name() == name)
+      .ok_or_else(|| UnknownHeuristic(name.to_string()))
+  }
+}
+
+impl From<Heuristic> for String {
+  fn from(heuristic: Heuristic) -> Self {
+    heuristic.name().to_string()
+  }
+}
+
+impl TryFrom<String> for Heuristic {
+  type Error = UnknownHeuristic;
+
+  fn try_from(name: String) -> Result<Self, Self::Error> {
+    name.parse()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownHeuristic(pub String);
+
+impl fmt::Display for UnknownHeuristic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "unknown heuristic {}, expected fcfs, edf or lci", self.0)
+  }
+}
+
+impl error::Error for UnknownHeuristic {}
+
+/// A plane a constructive heuristic could not land in its window, separated
+/// from the planes landed before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unschedulable {
+  pub plane_id: ID,
+}
+
+impl fmt::Display for Unschedulable {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "plane #{} cannot land in its window separated from the planes landed before it",
+      self.plane_id
+    )
+  }
+}
+
+impl error::Error for Unschedulable {}
+
+/// Index of the arrival with the smallest `key`
+fn first_by(candidates: &[Arrival], key: impl Fn(&Arrival) -> (u32, ID)) -> usize {
+  (0..candidates.len())
+    .min_by_key(|&k| key(&candidates[k]))
+    .unwrap()
+}
+
+impl LandingProblem {
+  /// Earliest time from `from` on at which `plane_id` can land on `runway`
+  /// separated from every arrival of `scheduled`, `None` if there is no such
+  /// time in its window
+  pub fn earliest_separated_landing(
+    &self,
+    scheduled: &Solution,
+    plane_id: ID,
+    runway: Runway,
+    from: u32,
+  ) -> Option<u32> {
+    let latest = *self.landing_window(plane_id).end();
+    let mut time = from;
+    'search: while time <= latest {
+      let arrival = Arrival::on_runway(plane_id, time, runway);
+      let first = scheduled.partition_point(|b| b.landing_time + self.max_separation <= time);
+      let near = scheduled[first..]
+        .iter()
+        .take_while(|b| b.landing_time < time + self.max_separation);
+      for b in near {
+        let conflict = if b.landing_time <= time {
+          self.conflict_between(b, &arrival)
+        } else {
+          self.conflict_between(&arrival, b)
+        };
+        if conflict > 0 {
+          // Lands after `b` instead
+          time = b.landing_time + self.separation_between(b, &arrival);
+          continue 'search;
+        }
+      }
+      return Some(time);
+    }
+    None
+  }
+
+  /// Latest time until `until` at which `plane_id` can land on `runway`
+  /// separated from every arrival of `scheduled`, `None` if there is no such
+  /// time in its window
+  pub fn latest_separated_landing(
+    &self,
+    scheduled: &Solution,
+    plane_id: ID,
+    runway: Runway,
+    until: u32,
+  ) -> Option<u32> {
+    let earliest = *self.landing_window(plane_id).start();
+    let mut time = until;
+    'search: while time >= earliest {
+      let arrival = Arrival::on_runway(plane_id, time, runway);
+      let first = scheduled.partition_point(|b| b.landing_time + self.max_separation <= time);
+      let near = scheduled[first..]
+        .iter()
+        .take_while(|b| b.landing_time < time + self.max_separation);
+      for b in near {
+        let conflict = if b.landing_time <= time {
+          self.conflict_between(b, &arrival)
+        } else {
+          self.conflict_between(&arrival, b)
+        };
+        if conflict > 0 {
+          // Lands before `b` instead
+          let separation = self.separation_between(&arrival, b).max(1);
+          time = b.landing_time.checked_sub(separation)?;
+          continue 'search;
+        }
+      }
+      return Some(time);
+    }
+    None
+  }
+
+  /// Runways `plane_id` can land on
+  pub(crate) fn allowed_runways(&self, plane_id: ID) -> impl Iterator<Item = Runway> {
+    match self.frozen(plane_id) {
+      Some(frozen) => frozen.runway..=frozen.runway,
+      None => 0..=self.runways - 1,
+    }
+  }
+
+  /// Arrival of `plane_id` added to `scheduled` at its target time, or the
+  /// first separated time after it, or else the first separated time of its
+  /// window, on the runway where it lands the earliest. `None` if it cannot
+  /// land separated from `scheduled`
+  pub fn separated_insertion(&self, scheduled: &Solution, plane_id: ID) -> Option<Arrival> {
+    let window = self.landing_window(plane_id);
+    let target = self.planes[plane_id].target_landing;
+    let target = target.clamp(*window.start(), *window.end());
+    self
+      .allowed_runways(plane_id)
+      .filter_map(|runway| {
+        self
+          .earliest_separated_landing(scheduled, plane_id, runway, target)
+          .or_else(|| self.earliest_separated_landing(scheduled, plane_id, runway, *window.start()))
+          .map(|time| Arrival::on_runway(plane_id, time, runway))
+      })
+      .min_by_key(|arrival| arrival.landing_time)
+  }
+
+  /// Arrival of `plane_id` added to `scheduled` at the earliest separated time
+  /// of its window, on the runway where that is the earliest
+  pub fn earliest_insertion(&self, scheduled: &Solution, plane_id: ID) -> Option<Arrival> {
+    let earliest = *self.landing_window(plane_id).start();
+    self
+      .allowed_runways(plane_id)
+      .filter_map(|runway| {
+        self
+          .earliest_separated_landing(scheduled, plane_id, runway, earliest)
+          .map(|time| Arrival::on_runway(plane_id, time, runway))
+      })
+      .min_by_key(|arrival| arrival.landing_time)
+  }
+
+  /// Arrival of `plane_id` added to `scheduled` at the separated time closest
+  /// to its target on either side, and on the runway, that costs the least
+  pub fn cheapest_insertion(&self, scheduled: &Solution, plane_id: ID) -> Option<Arrival> {
+    let plane = &self.planes[plane_id];
+    let window = self.landing_window(plane_id);
+    let target = plane.target_landing.clamp(*window.start(), *window.end());
+    self
+      .allowed_runways(plane_id)
+      .flat_map(|runway| {
+        [
+          self.earliest_separated_landing(scheduled, plane_id, runway, target),
+          self.latest_separated_landing(scheduled, plane_id, runway, target),
+        ]
+        .into_iter()
+        .flatten()
+        .map(move |time| Arrival::on_runway(plane_id, time, runway))
+      })
+      .min_by(|a, b| {
+        let cost = |arrival: &Arrival| plane.cost_for_landing(arrival.landing_time);
+        cost(a).partial_cmp(&cost(b)).unwrap()
+      })
+  }
+
+  /// Lands the planes one at a time, each at the arrival `insertion` gives
+  /// it, in the order `choose` picks them from the arrivals of the planes
+  /// left. Fails on the first plane `insertion` cannot land
+  fn land_one_by_one(
+    &self,
+    insertion: impl Fn(&Solution, ID) -> Option<Arrival>,
+    mut choose: impl FnMut(&[Arrival]) -> usize,
+  ) -> Result<Solution, Unschedulable> {
+    let mut solution = Solution::new();
+    let insert = |solution: &Solution, plane_id: ID| {
+      insertion(solution, plane_id).ok_or(Unschedulable { plane_id })
+    };
+    // Where every plane left would land. Adding an arrival only takes times
+    // away, so only the arrivals in conflict with it change
+    let mut candidates: Vec<Arrival> = self
+      .scheduled_planes()
+      .map(|plane| insert(&solution, plane.id))
+      .try_collect()?;
+    while !candidates.is_empty() {
+      let arrival = candidates.swap_remove(choose(&candidates));
+      let index = solution.partition_point(|a| a.landing_time <= arrival.landing_time);
+      solution.insert(index, arrival);
+
+      for candidate in &mut candidates {
+        let conflict = if arrival.landing_time <= candidate.landing_time {
+          self.conflict_between(&arrival, candidate)
+        } else {
+          self.conflict_between(candidate, &arrival)
+        };
+        if conflict > 0 {
+          *candidate = insert(&solution, candidate.plane_id)?;
+        }
+      }
+    }
+    Ok(solution)
+  }
+
+  /// Schedule built by `heuristic`, which passes `is_valid`
+  pub fn construct(&self, heuristic: Heuristic) -> Result<Solution, Unschedulable> {
+    match heuristic {
+      Heuristic::FirstComeFirstServed => self.land_one_by_one(
+        |scheduled, plane_id| self.separated_insertion(scheduled, plane_id),
+        |candidates| {
+          first_by(candidates, |arrival| {
+            let plane = &self.planes[arrival.plane_id];
+            (plane.target_landing, plane.id)
+          })
+        },
+      ),
+      Heuristic::EarliestDeadlineFirst => self.land_one_by_one(
+        |scheduled, plane_id| self.earliest_insertion(scheduled, plane_id),
+        |candidates| {
+          first_by(candidates, |arrival| {
+            let window = self.landing_window(arrival.plane_id);
+            (*window.end(), arrival.plane_id)
+          })
+        },
+      ),
+      Heuristic::LowestCostInsertion => self.land_one_by_one(
+        |scheduled, plane_id| self.cheapest_insertion(scheduled, plane_id),
+        |candidates| {
+          let cost = |arrival: &Arrival| {
+            self.planes[arrival.plane_id].cost_for_landing(arrival.landing_time)
+          };
+          (0..candidates.len())
+            .min_by(|&i, &j| {
+              let (a, b) = (&candidates[i], &candidates[j]);
+              cost(a)
+                .partial_cmp(&cost(b))
+                .unwrap()
+                .then(a.plane_id.cmp(&b.plane_id))
+            })
+            .unwrap()
+        },
+      ),
+    }
+  }
+}
+
+/// Lands the planes one at a time, choosing among those that would land the
+/// soonest. Planes that cannot land separated from the others land at the end
+/// of their window
+impl RandomizedConstruction<Solution> for LandingProblem {
+  fn greedy_randomized<R: Rng + ?Sized>(&self, alpha: f64, rng: &mut R) -> Solution {
+    let insertion = |scheduled: &Solution, plane_id| {
+      let arrival = self
+        .separated_insertion(scheduled, plane_id)
+        .unwrap_or_else(|| {
+          let latest = *self.landing_window(plane_id).end();
+          let runway = self.allowed_runways(plane_id).next().unwrap();
+          Arrival::on_runway(plane_id, latest, runway)
+        });
+      Some(arrival)
+    };
+    let choose = |candidates: &[Arrival]| {
+      let times = candidates.iter().map(|arrival| arrival.landing_time);
+      let (best, worst) = (times.clone().min().unwrap(), times.max().unwrap());
+      let threshold = best as f64 + alpha * (worst - best) as f64;
+      let restricted: Vec<usize> = (0..candidates.len())
+        .filter(|&k| candidates[k].landing_time as f64 <= threshold)
+        .collect();
+      *restricted.choose(rng).unwrap()
+    };
+    // Every plane gets an arrival, so none fails
+    self.land_one_by_one(insertion, choose).unwrap()
+  }
+}
@@ -0,0 +1,188 @@
+use crate::metaheuristics::{Problem, Recombinable};
+use crate::permutation::{Permutation, PermutationProblem};
+use crate::problem::{Arrival, LandingProblem, Runway, Solution, ID};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crossover {
+  /// Order crossover (OX): a segment of the first parent, the other planes in
+  /// the order of the second one
+  Order,
+  /// Partially-mapped crossover (PMX): a segment of the first parent, the
+  /// other planes at their position in the second one, unless taken by the
+  /// segment
+  PartiallyMapped,
+  /// Every plane lands somewhere between its landing times, or positions, in
+  /// both parents
+  Blend,
+}
+
+impl Crossover {
+  pub const ALL: [Crossover; 3] = [
+    Crossover::Order,
+    Crossover::PartiallyMapped,
+    Crossover::Blend,
+  ];
+}
+
+/// Bounds `i <= j` of a random segment of `n` elements
+fn segment<R: Rng + ?Sized>(n: usize, rng: &mut R) -> (usize, usize) {
+  let i = rng.gen_range(0..=n);
+  let j = rng.gen_range(0..=n);
+  (i.min(j), i.max(j))
+}
+
+pub fn order_crossover<R: Rng + ?Sized>(a: &[ID], b: &[ID], rng: &mut R) -> Permutation {
+  let n = a.len();
+  let (i, j) = segment(n, rng);
+  let kept: HashSet<ID> = a[i..j].iter().copied().collect();
+  let mut others = b[j..]
+    .iter()
+    .chain(&b[..j])
+    .filter(|plane_id| !kept.contains(plane_id));
+  let mut child = a.to_vec();
+  for k in (j..n).chain(0..i) {
+    child[k] = *others.next().unwrap();
+  }
+  child
+}
+
+pub fn partially_mapped_crossover<R: Rng + ?Sized>(a: &[ID], b: &[ID], rng: &mut R) -> Permutation {
+  let (i, j) = segment(a.len(), rng);
+  // Position of the planes of the segment
+  let kept: HashMap<ID, usize> = (i..j).map(|k| (a[k], k)).collect();
+  let mut child = b.to_vec();
+  child[i..j].copy_from_slice(&a[i..j]);
+  for k in (0..i).chain(j..a.len()) {
+    // Follows the mapping of the segment until a plane outside of it
+    let mut plane_id = b[k];
+    while let Some(&k) = kept.get(&plane_id) {
+      plane_id = b[k];
+    }
+    child[k] = plane_id;
+  }
+  child
+}
+
+/// Planes ordered by a random blend of their positions in both parents
+pub fn blend_crossover<R: Rng + ?Sized>(a: &[ID], b: &[ID], rng: &mut R) -> Permutation {
+  let position_in_b: HashMap<ID, usize> = b.iter().enumerate().map(|(k, &id)| (id, k)).collect();
+  let mut keys: Vec<(f64, ID)> = a
+    .iter()
+    .enumerate()
+    .map(|(k, &plane_id)| {
+      let (k_a, k_b) = (k as f64, position_in_b[&plane_id] as f64);
+      (k_a + rng.gen_range(0.0..=1.0) * (k_b - k_a), plane_id)
+    })
+    .collect();
+  keys.sort_by(|(x, _), (y, _)| x.partial_cmp(y).unwrap());
+  keys.into_iter().map(|(_, plane_id)| plane_id).collect()
+}
+
+impl LandingProblem {
+  /// Lands the planes in the order and on the runways of `sequence`, every
+  /// one as close to its target as the planes before it allow. Planes that
+  /// cannot land in their window this way are left in conflict
+  pub fn land_in_order(&self, sequence: &[(ID, Runway)]) -> Solution {
+    let mut times: Vec<u32> = Vec::with_capacity(sequence.len());
+    for (l, &(plane_id, _)) in sequence.iter().enumerate() {
+      let window = self.landing_window(plane_id);
+      let target = self.planes[plane_id].target_landing;
+      let time = self
+        .earliest_landing_in_sequence(&sequence[..=l], &times)
+        .max(target)
+        .min(*window.end());
+      times.push(time);
+    }
+    let mut solution: Solution = sequence
+      .iter()
+      .zip(times)
+      .map(|(&(plane_id, runway), time)| Arrival::on_runway(plane_id, time, runway))
+      .collect();
+    solution.sort_by_key(|a| a.landing_time);
+    solution
+  }
+
+  /// Every plane lands at a random time between its landing times in `a` and
+  /// `b`, on the runway of either
+  fn blend_landing_times<R: Rng + ?Sized>(
+    &self,
+    a: &Solution,
+    b: &Solution,
+    rng: &mut R,
+  ) -> Solution {
+    let in_b: HashMap<ID, &Arrival> = b
+      .iter()
+      .map(|arrival| (arrival.plane_id, arrival))
+      .collect();
+    let mut child: Solution = a
+      .iter()
+      .map(|arrival| {
+        let other = in_b[&arrival.plane_id];
+        if self.frozen(arrival.plane_id).is_some() {
+          return *arrival;
+        }
+        let (t_a, t_b) = (arrival.landing_time as f64, other.landing_time as f64);
+        let time = t_a + rng.gen_range(0.0..=1.0) * (t_b - t_a);
+        let runway = if rng.gen_bool(0.5) {
+          arrival.runway
+        } else {
+          other.runway
+        };
+        Arrival::on_runway(arrival.plane_id, time.round() as u32, runway)
+      })
+      .collect();
+    child.sort_by_key(|a| a.landing_time);
+    child
+  }
+}
+
+/// Order crossovers recombine the landing sequences, planes keeping the
+/// runway of the first parent, blend crossover recombines the landing times
+impl Recombinable<Solution> for LandingProblem {
+  type Crossover = Crossover;
+
+  fn crossover<R: Rng + ?Sized>(
+    &self,
+    crossover: Crossover,
+    a: &Solution,
+    b: &Solution,
+    rng: &mut R,
+  ) -> Solution {
+    let order_a = PermutationProblem::from_solution(a);
+    let order_b = PermutationProblem::from_solution(b);
+    let order = match crossover {
+      Crossover::Order => order_crossover(&order_a, &order_b, rng),
+      Crossover::PartiallyMapped => partially_mapped_crossover(&order_a, &order_b, rng),
+      Crossover::Blend => return self.blend_landing_times(a, b, rng),
+    };
+    let runways: HashMap<ID, Runway> = a
+      .iter()
+      .map(|arrival| (arrival.plane_id, arrival.runway))
+      .collect();
+    let sequence: Vec<(ID, Runway)> = order
+      .into_iter()
+      .map(|plane_id| (plane_id, runways[&plane_id]))
+      .collect();
+    self.land_in_order(&sequence)
+  }
+}
+
+impl Recombinable<Permutation> for PermutationProblem<'_> {
+  type Crossover = Crossover;
+
+  fn crossover<R: Rng + ?Sized>(
+    &self,
+    crossover: Crossover,
+    a: &Permutation,
+    b: &Permutation,
+    rng: &mut R,
+  ) -> Permutation {
+    match crossover {
+      Crossover::Order => order_crossover(a, b, rng),
+      Crossover::PartiallyMapped => partially_mapped_crossover(a, b, rng),
+      Crossover::Blend => blend_crossover(a, b, rng),
+    }
+  }
+}
@@ -0,0 +1,446 @@
+use crate::construction::Heuristic;
+use crate::problem::{LandingProblem, Runway, Solution, ID};
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::iter::zip;
+use std::time::{Duration, Instant};
+
+const EPSILON: f64 = 1e-9;
+
+/// When `branch_and_bound` gives up on proving optimality
+#[derive(Debug, Clone, Copy)]
+pub struct BranchAndBoundLimits {
+  /// Nodes expanded at most
+  pub nodes: usize,
+  pub time: Duration,
+}
+
+impl Default for BranchAndBoundLimits {
+  fn default() -> Self {
+    BranchAndBoundLimits {
+      nodes: usize::MAX,
+      time: Duration::MAX,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExactSolution {
+  /// Best valid schedule found, `None` if there is none
+  pub solution: Option<Solution>,
+  /// Landing cost of `solution`, infinite without one
+  pub cost: f64,
+  /// No schedule costs less
+  pub bound: f64,
+  /// Whether the search was completed, proving `solution` optimal, or that
+  /// there is no valid schedule
+  pub optimal: bool,
+  pub nodes: usize,
+  pub duration: Duration,
+}
+
+impl ExactSolution {
+  /// Relative gap between `cost` and `bound`, 0 once proven optimal, and
+  /// infinite without a schedule
+  pub fn gap(&self) -> f64 {
+    if self.solution.is_none() {
+      f64::INFINITY
+    } else if self.cost - self.bound <= EPSILON {
+      0.0
+    } else {
+      (self.cost - self.bound) / self.cost.abs().max(EPSILON)
+    }
+  }
+}
+
+/// Landing sequence, in landing time order, with the earliest landing times
+/// it allows
+#[derive(Debug, Clone)]
+struct Node {
+  sequence: Vec<(ID, Runway)>,
+  earliest: Vec<u32>,
+  bound: f64,
+}
+
+struct Search<'a> {
+  problem: &'a LandingProblem,
+  planes: Vec<ID>,
+  /// Planes landing before each plane in some optimal schedule
+  predecessors: Vec<Vec<ID>>,
+  best: Option<Solution>,
+  best_cost: f64,
+  /// Runway sequences already reached, hashed, when runways do not constrain
+  /// each other. The sequences interleaving them in another order give the
+  /// same schedules
+  visited: Option<HashSet<u128>>,
+}
+
+impl Search<'_> {
+  /// `node` with `plane_id` landing next on `runway`, `None` if that cannot
+  /// beat the best schedule
+  fn child(&mut self, node: &Node, plane_id: ID, runway: Runway) -> Option<Node> {
+    let mut sequence = node.sequence.clone();
+    let mut earliest = node.earliest.clone();
+    sequence.push((plane_id, runway));
+    if let Some(visited) = &mut self.visited {
+      if !visited.insert(runway_sequences_hash(&sequence, self.problem.runways)) {
+        return None;
+      }
+    }
+    let time = self
+      .problem
+      .earliest_landing_in_sequence(&sequence, &earliest);
+    if time > *self.problem.landing_window(plane_id).end() {
+      return None;
+    }
+    earliest.push(time);
+
+    // The sequence landed optimally, and a bound on the planes left
+    let mut landed = vec![false; self.problem.planes.len()];
+    for &(id, _) in &sequence {
+      landed[id] = true;
+    }
+    let left: Vec<(ID, u32)> = self
+      .planes
+      .iter()
+      .filter(|&&id| !landed[id])
+      .map(|&id| Some((id, self.earliest_after(&mut sequence, &earliest, id)?)))
+      .try_collect()?;
+    let bound_left = self.bound_left(&left)?;
+    // The planes of the sequence at their cheapest time from their earliest
+    // one bound its cost too, without timing it
+    let quick_bound: f64 = zip(&sequence, &earliest)
+      .map(|(&(id, _), &from)| {
+        self.problem.planes[id].cost_for_landing(self.cheapest_time(id, from))
+      })
+      .sum();
+    if quick_bound + bound_left >= self.best_cost - EPSILON {
+      return None;
+    }
+    let timed = self.problem.timing_on_runways(&sequence).ok()?;
+    let bound = self.problem.landing_cost(&timed) + bound_left;
+    if bound >= self.best_cost - EPSILON {
+      return None;
+    }
+    if sequence.len() == self.planes.len() {
+      self.best = Some(timed);
+      self.best_cost = bound;
+      return None;
+    }
+    Some(Node {
+      sequence,
+      earliest,
+      bound,
+    })
+  }
+
+  /// Earliest time `plane_id` can land after `sequence` on any runway,
+  /// `None` if it cannot land in its window
+  fn earliest_after(
+    &self,
+    sequence: &mut Vec<(ID, Runway)>,
+    earliest: &[u32],
+    plane_id: ID,
+  ) -> Option<u32> {
+    let latest = *self.problem.landing_window(plane_id).end();
+    self
+      .problem
+      .allowed_runways(plane_id)
+      .map(|runway| {
+        sequence.push((plane_id, runway));
+        let time = self
+          .problem
+          .earliest_landing_in_sequence(sequence, earliest);
+        sequence.pop();
+        time
+      })
+      .min()
+      .filter(|&time| time <= latest)
+  }
+
+  /// Cheapest time for `plane_id` to land from `from` on
+  fn cheapest_time(&self, plane_id: ID, from: u32) -> u32 {
+    let latest = *self.problem.landing_window(plane_id).end();
+    from
+      .max(self.problem.planes[plane_id].target_landing)
+      .min(latest)
+  }
+
+  /// Lower bound on the cost of the planes left, each landing from the given
+  /// time on. On a single runway, pairs of planes also pay for being
+  /// separated, over pairs chosen greedily among those that cost the most
+  fn bound_left(&self, left: &[(ID, u32)]) -> Option<f64> {
+    let mut bound: f64 = left
+      .iter()
+      .map(|&(id, from)| self.problem.planes[id].cost_for_landing(self.cheapest_time(id, from)))
+      .sum();
+    if self.problem.runways > 1 {
+      return Some(bound);
+    }
+    let mut pairs = Vec::new();
+    for (k, &a) in left.iter().enumerate() {
+      for &b in &left[k + 1..] {
+        let extra = match (self.separation_cost(a, b), self.separation_cost(b, a)) {
+          (None, None) => return None,
+          (Some(x), Some(y)) => x.min(y),
+          (x, y) => x.or(y).unwrap(),
+        };
+        if extra > 0.0 {
+          pairs.push((extra, a.0, b.0));
+        }
+      }
+    }
+    pairs.sort_by(|x, y| y.0.partial_cmp(&x.0).unwrap());
+    let mut paired = vec![false; self.problem.planes.len()];
+    for (extra, a, b) in pairs {
+      if !paired[a] && !paired[b] {
+        paired[a] = true;
+        paired[b] = true;
+        bound += extra;
+      }
+    }
+    Some(bound)
+  }
+
+  /// Least extra cost, over their cheapest times, for `b` to land separated
+  /// after `a`, by landing `a` earlier or `b` later. Both land from the given
+  /// times on. `None` if they cannot land in that order
+  fn separation_cost(&self, (a, from_a): (ID, u32), (b, from_b): (ID, u32)) -> Option<f64> {
+    let (time_a, time_b) = (self.cheapest_time(a, from_a), self.cheapest_time(b, from_b));
+    let separation = self.problem.separation_time_between(a, b) as i64;
+    let missing = separation - (time_b as i64 - time_a as i64);
+    if missing <= 0 {
+      return Some(0.0);
+    }
+    // Past their cheapest time, costs grow linearly
+    let earlier = (
+      (time_a - from_a) as i64,
+      self.problem.planes[a].penalty_before,
+    );
+    let later = (
+      (*self.problem.landing_window(b).end() - time_b) as i64,
+      self.problem.planes[b].penalty_after,
+    );
+    if earlier.0 + later.0 < missing {
+      return None;
+    }
+    let (cheap, dear) = if earlier.1 <= later.1 {
+      (earlier, later)
+    } else {
+      (later, earlier)
+    };
+    let from_cheap = missing.min(cheap.0);
+    Some(from_cheap as f64 * cheap.1 + (missing - from_cheap) as f64 * dear.1)
+  }
+
+  /// Children of `node`, the most promising last. Planes are only landed
+  /// once their predecessors have, and a plane only opens the first of the
+  /// runways still empty, as these are interchangeable
+  fn children(&mut self, node: &Node) -> Vec<Node> {
+    let mut landed = vec![false; self.problem.planes.len()];
+    for &(id, _) in &node.sequence {
+      landed[id] = true;
+    }
+    let left: Vec<ID> = self
+      .planes
+      .iter()
+      .copied()
+      .filter(|&id| !landed[id])
+      .filter(|&id| self.predecessors[id].iter().all(|&before| landed[before]))
+      .collect();
+    let used = node
+      .sequence
+      .iter()
+      .map(|&(_, runway)| runway + 1)
+      .max()
+      .unwrap_or(0);
+    let symmetric = self.problem.horizon.is_none();
+
+    let mut children = Vec::new();
+    for &plane_id in &left {
+      for runway in self.problem.allowed_runways(plane_id) {
+        if symmetric && runway > used {
+          break;
+        }
+        children.extend(self.child(node, plane_id, runway));
+      }
+    }
+    children.sort_by(|a, b| b.bound.partial_cmp(&a.bound).unwrap());
+    children
+  }
+}
+
+fn runway_sequences_hash(sequence: &[(ID, Runway)], runways: usize) -> u128 {
+  let mut runway_sequences = vec![Vec::new(); runways];
+  for &(plane_id, runway) in sequence {
+    runway_sequences[runway].push(plane_id);
+  }
+  // Two hashes keep collisions out of reach
+  let mut hashers = [DefaultHasher::new(), DefaultHasher::new()];
+  hashers[1].write_u8(1);
+  for hasher in &mut hashers {
+    runway_sequences.hash(hasher);
+  }
+  ((hashers[0].finish() as u128) << 64) | hashers[1].finish() as u128
+}
+
+/// Whether `a` and `b` have the same separation times from every other
+/// plane, and from each other, and the same penalties
+fn same_type(problem: &LandingProblem, a: ID, b: ID) -> bool {
+  let same_separations = |separation: &dyn Fn(ID, ID) -> u32| {
+    separation(a, b) == separation(b, a)
+      && (0..problem.planes.len())
+        .filter(|&k| k != a && k != b)
+        .all(|k| separation(a, k) == separation(b, k) && separation(k, a) == separation(k, b))
+  };
+  let (plane_a, plane_b) = (&problem.planes[a], &problem.planes[b]);
+  plane_a.penalty_before == plane_b.penalty_before
+    && plane_a.penalty_after == plane_b.penalty_after
+    && same_separations(&|i, j| problem.separation_time_between(i, j))
+    && problem
+      .cross_runway_separation
+      .as_ref()
+      .is_none_or(|separation_times| same_separations(&|i, j| separation_times[i][j]))
+}
+
+/// Planes that land before each plane in some optimal schedule: those whose
+/// window closes before it opens, and the planes of the same type with a
+/// window and a target time that are not later. Exchanging the landings of
+/// two such planes otherwise would not raise the cost
+fn predecessors(problem: &LandingProblem, planes: &[ID]) -> Vec<Vec<ID>> {
+  let mut predecessors = vec![Vec::new(); problem.planes.len()];
+  for &a in planes {
+    for &b in planes.iter().filter(|&&b| b != a) {
+      let (window_a, window_b) = (problem.landing_window(a), problem.landing_window(b));
+      let key = |plane_id: ID, window: &std::ops::RangeInclusive<u32>| {
+        let target = problem.planes[plane_id].target_landing;
+        (*window.start(), target, *window.end())
+      };
+      let (key_a, key_b) = (key(a, &window_a), key(b, &window_b));
+      let not_later = key_a.0 <= key_b.0 && key_a.1 <= key_b.1 && key_a.2 <= key_b.2;
+      let dominates = problem.horizon.is_none()
+        && not_later
+        && (key_a != key_b || a < b)
+        && same_type(problem, a, b);
+      if window_a.end() < window_b.start() || dominates {
+        predecessors[b].push(a);
+      }
+    }
+  }
+  predecessors
+}
+
+/// Depth-first branch and bound over landing sequences, each timed optimally
+/// with `timing_on_runways`. A sequence is bounded by its cost, plus the
+/// cost of every plane left landing at its cheapest time after it, ignoring
+/// the separations between them but for a few pairs on a single runway.
+/// Starts from `incumbent` if it is valid, and from the constructive
+/// heuristics. When stopped by `limits`, `bound` is the lowest bound of the
+/// sequences left to explore
+pub fn branch_and_bound(
+  problem: &LandingProblem,
+  incumbent: Option<&Solution>,
+  limits: BranchAndBoundLimits,
+) -> ExactSolution {
+  let start = Instant::now();
+  let planes: Vec<ID> = problem.scheduled_planes().map(|plane| plane.id).collect();
+  let mut search = Search {
+    problem,
+    predecessors: predecessors(problem, &planes),
+    planes,
+    best: None,
+    best_cost: f64::INFINITY,
+    visited: (problem.runways > 1 && problem.cross_runway_separation.is_none()).then(HashSet::new),
+  };
+  let constructed = Heuristic::ALL
+    .into_iter()
+    .filter_map(|heuristic| problem.construct(heuristic).ok());
+  for solution in incumbent.cloned().into_iter().chain(constructed) {
+    let cost = problem.landing_cost(&solution);
+    if problem.is_valid(&solution) && cost < search.best_cost {
+      search.best = Some(solution);
+      search.best_cost = cost;
+    }
+  }
+
+  let mut stack = vec![Node {
+    sequence: Vec::new(),
+    earliest: Vec::new(),
+    bound: 0.0,
+  }];
+  if search.planes.is_empty() {
+    search.best = Some(Solution::new());
+    search.best_cost = 0.0;
+    stack.clear();
+  }
+  let mut nodes = 0;
+  while let Some(node) = stack.pop() {
+    if node.bound >= search.best_cost - EPSILON {
+      continue;
+    }
+    if nodes >= limits.nodes || start.elapsed() >= limits.time {
+      stack.push(node);
+      break;
+    }
+    nodes += 1;
+    let children = search.children(&node);
+    stack.extend(children);
+  }
+
+  let open_bound = stack
+    .iter()
+    .map(|node| node.bound)
+    .filter(|&bound| bound < search.best_cost - EPSILON)
+    .min_by(|a, b| a.partial_cmp(b).unwrap());
+  ExactSolution {
+    bound: open_bound.unwrap_or(search.best_cost),
+    optimal: open_bound.is_none(),
+    solution: search.best,
+    cost: search.best_cost,
+    nodes,
+    duration: start.elapsed(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parser::parse_problem_data;
+
+  fn airland1() -> LandingProblem {
+    let file_path = concat!(env!("CARGO_MANIFEST_DIR"), "/data/airland1.txt");
+    LandingProblem::from_parser(parse_problem_data(file_path).unwrap())
+  }
+
+  #[test]
+  fn proves_airland1_optimal() {
+    let problem = airland1();
+    let exact = branch_and_bound(&problem, None, BranchAndBoundLimits::default());
+    assert_eq!(exact.cost, 700.0);
+    assert_eq!(exact.bound, 700.0);
+    assert!(exact.optimal);
+    assert_eq!(exact.gap(), 0.0);
+    let solution = exact.solution.unwrap();
+    assert!(problem.is_valid(&solution));
+    assert_eq!(problem.landing_cost(&solution), 700.0);
+  }
+
+  #[test]
+  fn gap_without_a_schedule() {
+    let exact = ExactSolution {
+      solution: None,
+      cost: f64::INFINITY,
+      bound: 500.0,
+      optimal: false,
+      nodes: 1,
+      duration: Duration::ZERO,
+    };
+    assert_eq!(exact.gap(), f64::INFINITY);
+    let infeasible = ExactSolution {
+      bound: f64::INFINITY,
+      optimal: true,
+      ..exact
+    };
+    assert_eq!(infeasible.gap(), f64::INFINITY);
+  }
+}
@@ -0,0 +1,253 @@
+use crate::parser::{Plane, ProblemData};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::ops::RangeInclusive;
+use std::{error, fmt};
+
+/// Separation time of a plane after itself, as written in the OR-Library
+/// files
+const SELF_SEPARATION: u32 = 99999;
+
+/// Wake turbulence category of an aircraft, indexing the separation matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WakeClass {
+  Heavy,
+  Medium,
+  Light,
+}
+
+pub const WAKE_CLASSES: [WakeClass; 3] = [WakeClass::Heavy, WakeClass::Medium, WakeClass::Light];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorSettings {
+  pub planes: usize,
+  /// Target times are drawn uniformly over this many time units
+  pub target_spread: u32,
+  /// Earliest landing time before the target time
+  pub before_target: RangeInclusive<u32>,
+  /// Latest landing time after the target time
+  pub after_target: RangeInclusive<u32>,
+  /// Appearance time before the earliest landing time
+  pub lead_time: RangeInclusive<u32>,
+  /// Per time unit, rounded to cents as in the OR-Library files
+  pub penalty_before: RangeInclusive<f64>,
+  pub penalty_after: RangeInclusive<f64>,
+  /// Relative frequency of each class, in the order of `WAKE_CLASSES`
+  pub class_weights: [f64; 3],
+  /// Separation time needed by a plane of the column class landing after
+  /// one of the row class
+  pub separation: [[u32; 3]; 3],
+  pub freeze_time: u32,
+}
+
+impl Default for GeneratorSettings {
+  /// Windows, lead times and penalties in the ranges of the OR-Library
+  /// instances. Separations follow the ICAO wake turbulence distances (4, 5
+  /// and 6 NM behind a heavy, 5 NM between a medium and a light behind it,
+  /// 3 NM otherwise), doubled
+  fn default() -> Self {
+    GeneratorSettings {
+      planes: 100,
+      target_spread: 800,
+      before_target: 10..=100,
+      after_target: 200..=500,
+      lead_time: 60..=90,
+      penalty_before: 10.0..=30.0,
+      penalty_after: 10.0..=30.0,
+      class_weights: [0.2, 0.5, 0.3],
+      separation: [[8, 10, 12], [6, 6, 10], [6, 6, 6]],
+      freeze_time: 30,
+    }
+  }
+}
+
+impl GeneratorSettings {
+  /// Checks that instances can be drawn from the settings
+  pub fn check(&self) -> Result<(), GeneratorError> {
+    let weights = &self.class_weights;
+    let total: f64 = weights.iter().sum();
+    let valid_weights = weights.iter().all(|w| w.is_finite() && *w >= 0.0);
+    if !(valid_weights && total.is_finite() && total > 0.0) {
+      return Err(GeneratorError::ClassWeights(*weights));
+    }
+    let ranges = [
+      ("before target", self.before_target.is_empty()),
+      ("after target", self.after_target.is_empty()),
+      ("lead time", self.lead_time.is_empty()),
+      ("penalty before", !is_finite_range(&self.penalty_before)),
+      ("penalty after", !is_finite_range(&self.penalty_after)),
+    ];
+    if let Some(&(name, _)) = ranges.iter().find(|(_, invalid)| *invalid) {
+      return Err(GeneratorError::Range(name));
+    }
+    // Latest landing of a plane with the largest target time, after the
+    // shift of `generate`
+    let latest = [
+      self.before_target.end(),
+      self.lead_time.end(),
+      &self.target_spread,
+      self.after_target.end(),
+    ]
+    .iter()
+    .map(|&&time| time as u64)
+    .sum::<u64>();
+    if latest > u32::MAX as u64 {
+      return Err(GeneratorError::TimeOverflow(latest));
+    }
+    Ok(())
+  }
+}
+
+fn is_finite_range(range: &RangeInclusive<f64>) -> bool {
+  range.start().is_finite() && range.end().is_finite() && !range.is_empty()
+}
+
+/// Settings no instance can be drawn from
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratorError {
+  /// Class weights must be finite and non-negative, with a finite and
+  /// positive sum
+  ClassWeights([f64; 3]),
+  /// A range that is empty or has an infinite bound
+  Range(&'static str),
+  /// Latest landing time that could be drawn, above `u32::MAX`
+  TimeOverflow(u64),
+}
+
+impl fmt::Display for GeneratorError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      GeneratorError::ClassWeights(weights) => write!(
+        f,
+        "class weights {:?} must be finite and non-negative, with a finite positive sum",
+        weights
+      ),
+      GeneratorError::Range(name) => write!(f, "the {} range is empty or not finite", name),
+      GeneratorError::TimeOverflow(latest) => write!(
+        f,
+        "planes could land at {}, after the largest time {}",
+        latest,
+        u32::MAX
+      ),
+    }
+  }
+}
+
+impl error::Error for GeneratorError {}
+
+/// A random instance following `settings`, the same for the same state of
+/// `rng`. Times are shifted so that the earliest appearance time can be 0
+pub fn generate<R: Rng + ?Sized>(
+  settings: &GeneratorSettings,
+  rng: &mut R,
+) -> Result<ProblemData, GeneratorError> {
+  settings.check()?;
+  let offset = settings.before_target.end() + settings.lead_time.end();
+  let penalty = |range: &RangeInclusive<f64>, rng: &mut R| {
+    (rng.gen_range(range.clone()) * 100.0).round() / 100.0
+  };
+  let classes: Vec<WakeClass> = (0..settings.planes)
+    .map(|_| {
+      WAKE_CLASSES
+        .choose_weighted(rng, |&class| settings.class_weights[class as usize])
+        .copied()
+        .map_err(|_| GeneratorError::ClassWeights(settings.class_weights))
+    })
+    .try_collect()?;
+  let planes = classes
+    .iter()
+    .enumerate()
+    .map(|(i, &class)| {
+      let target_landing = offset + rng.gen_range(0..=settings.target_spread);
+      let earliest_landing = target_landing - rng.gen_range(settings.before_target.clone());
+      let latest_landing = target_landing + rng.gen_range(settings.after_target.clone());
+      let appearance_time = earliest_landing - rng.gen_range(settings.lead_time.clone());
+      let separation_times = classes
+        .iter()
+        .enumerate()
+        .map(|(j, &follower)| {
+          if i == j {
+            SELF_SEPARATION
+          } else {
+            settings.separation[class as usize][follower as usize]
+          }
+        })
+        .collect();
+      Plane {
+        appearance_time,
+        earliest_landing,
+        target_landing,
+        latest_landing,
+        penalty_before: penalty(&settings.penalty_before, rng),
+        penalty_after: penalty(&settings.penalty_after, rng),
+        separation_times,
+      }
+    })
+    .collect();
+  Ok(ProblemData {
+    num_planes: settings.planes,
+    freeze_time: settings.freeze_time,
+    planes,
+    replaced: Vec::new(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::rngs::StdRng;
+  use rand::SeedableRng;
+
+  #[test]
+  fn rejects_settings_it_cannot_draw_from() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for class_weights in [[f64::NAN, 1.0, 1.0], [0.0; 3], [-1.0, 2.0, 0.0], [1e308; 3]] {
+      let settings = GeneratorSettings {
+        class_weights,
+        ..GeneratorSettings::default()
+      };
+      assert!(matches!(
+        generate(&settings, &mut rng),
+        Err(GeneratorError::ClassWeights(_))
+      ));
+    }
+    let settings = GeneratorSettings {
+      penalty_after: 1.0..=f64::INFINITY,
+      ..GeneratorSettings::default()
+    };
+    assert_eq!(
+      generate(&settings, &mut rng).unwrap_err(),
+      GeneratorError::Range("penalty after")
+    );
+    let settings = GeneratorSettings {
+      planes: 2,
+      after_target: 4294967000..=u32::MAX,
+      ..GeneratorSettings::default()
+    };
+    assert!(matches!(
+      generate(&settings, &mut rng),
+      Err(GeneratorError::TimeOverflow(_))
+    ));
+    let settings = GeneratorSettings {
+      before_target: 0..=u32::MAX,
+      ..GeneratorSettings::default()
+    };
+    assert!(matches!(
+      generate(&settings, &mut rng),
+      Err(GeneratorError::TimeOverflow(_))
+    ));
+    // The latest landing time can be exactly the largest time
+    let settings = GeneratorSettings {
+      planes: 2,
+      target_spread: 0,
+      before_target: 0..=0,
+      lead_time: 0..=0,
+      after_target: u32::MAX..=u32::MAX,
+      ..GeneratorSettings::default()
+    };
+    let data = generate(&settings, &mut rng).unwrap();
+    assert_eq!(data.planes[0].latest_landing, u32::MAX);
+    let data = generate(&GeneratorSettings::default(), &mut rng).unwrap();
+    assert_eq!(data.planes.len(), 100);
+  }
+}
@@ -1,7 +1,21 @@
 #![allow(unused)]
-#![feature(array_windows)]
 #![feature(iterator_try_collect)]
 
+pub mod best_known;
+pub mod construction;
+pub mod crossover;
+pub mod exact;
+pub mod generator;
 pub mod metaheuristics;
+pub mod mip;
+pub mod neighborhoods;
+pub mod parallel;
 pub mod parser;
+pub mod permutation;
 pub mod problem;
+pub mod racing;
+pub mod report;
+pub mod simulation;
+pub mod statistics;
+pub mod timing;
+pub mod verification;
@@ -1,11 +1,38 @@
 #![feature(iterator_try_collect)]
-use airplane_landing_scheduler::metaheuristics::{simulated_annealing, Problem};
-use airplane_landing_scheduler::parser::parse_problem_data;
+use airplane_landing_scheduler::best_known::{gap, BestKnownTable};
+use airplane_landing_scheduler::construction::Heuristic;
+use airplane_landing_scheduler::crossover::Crossover;
+use airplane_landing_scheduler::exact::{branch_and_bound, BranchAndBoundLimits};
+use airplane_landing_scheduler::generator::{generate, GeneratorSettings};
+use airplane_landing_scheduler::metaheuristics::{
+  genetic_algorithm, grasp, hill_climb, ils_from, simulated_annealing, tabu_search, vns,
+  GeneticParameters, Problem,
+};
+use airplane_landing_scheduler::mip::SolutionError;
+use airplane_landing_scheduler::parallel::{available_workers, job_seed, run_parallel, Summary};
+use airplane_landing_scheduler::parser::{
+  parse_problem_data_with, parse_separation_matrix_from, ParseError, ParseMode,
+};
 use airplane_landing_scheduler::problem::*;
+use airplane_landing_scheduler::racing::{iterated_race, ParameterSpace, RaceSettings};
+use airplane_landing_scheduler::report::SolutionReport;
+use airplane_landing_scheduler::simulation::simulate;
+use airplane_landing_scheduler::statistics::{friedman, friedman_post_hoc, wilcoxon_signed_rank};
+use airplane_landing_scheduler::verification::{read_schedule_from, verify};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 use std::time::Duration;
-use std::{collections::HashSet, env::args, time};
+use std::{collections::HashSet, env::args, error, fmt, mem, process, time};
 
-fn display_solution(problem: &LandingProblem, solution: &Solution) {
+/// Level under which the compare command reports a difference as significant
+const SIGNIFICANCE: f64 = 0.05;
+
+fn display_costs(problem: &LandingProblem, solution: &Solution) {
   println!(
     "TotalCost={}\tLandingCost={}\tConflictCost={}\tValid={}",
     problem.cost(solution),
@@ -13,40 +40,36 @@ fn display_solution(problem: &LandingProblem, solution: &Solution) {
     problem.conflict_cost(solution),
     problem.is_valid(solution)
   );
+}
+
+fn display_solution(problem: &LandingProblem, solution: &Solution) {
+  display_costs(problem, solution);
   let conflicts = problem
     .conflicts(solution)
     .flat_map(|(a, b, _)| [a, b])
     .collect::<HashSet<_>>();
 
-  println!("-------------------------------------------------");
-  println!("| ID\t| Time\t| Conf.\t| Land.\t| Separation\t|");
-  println!("-------------------------------------------------");
+  println!("---------------------------------------------------------");
+  println!("| ID\t| Rwy\t| Time\t| Conf.\t| Land.\t| Separation\t|");
+  println!("---------------------------------------------------------");
   for (i, arrival) in solution.iter().enumerate() {
-    let sep = if i == 0 {
-      (
-        None,
-        Some(problem.separation_time_between(solution[i].plane_id, solution[i + 1].plane_id)),
-      )
-    } else if i == solution.len() - 1 {
-      (
-        Some(problem.separation_time_between(solution[i - 1].plane_id, solution[i].plane_id)),
-        None,
-      )
-    } else {
-      (
-        Some(problem.separation_time_between(solution[i - 1].plane_id, solution[i].plane_id)),
-        Some(problem.separation_time_between(solution[i].plane_id, solution[i + 1].plane_id)),
-      )
-    };
-    let sep = match sep {
-      (None, None) => unreachable!(),
-      (None, Some(y)) => format!("- , {}", y),
-      (Some(x), None) => format!("{} , -", x),
-      (Some(x), Some(y)) => format!("{} , {}", x, y),
+    let same_runway = |other: &&Arrival| other.runway == arrival.runway;
+    let previous = solution[..i].iter().rev().find(same_runway);
+    let next = solution[i + 1..].iter().find(same_runway);
+    let sep = match (previous, next) {
+      (None, None) => "- , -".to_string(),
+      (None, Some(y)) => format!("- , {}", problem.separation_between(arrival, y)),
+      (Some(x), None) => format!("{} , -", problem.separation_between(x, arrival)),
+      (Some(x), Some(y)) => format!(
+        "{} , {}",
+        problem.separation_between(x, arrival),
+        problem.separation_between(arrival, y)
+      ),
     };
     println!(
-      "| {}\t| {:<6}| {}\t| {:<4}\t| {:<7}\t|",
+      "| {}\t| {}\t| {:<6}| {}\t| {:<4}\t| {:<7}\t|",
       problem.planes[arrival.plane_id].id,
+      arrival.runway,
       arrival.landing_time,
       if conflicts.contains(arrival) {
         "*"
@@ -57,6 +80,19 @@ fn display_solution(problem: &LandingProblem, solution: &Solution) {
       sep
     );
   }
+  for (a, b, duration) in problem.conflicts(solution) {
+    let position = |arrival: &Arrival| solution.iter().position(|x| x == arrival).unwrap();
+    let adjacent = solution[position(&a) + 1..position(&b)]
+      .iter()
+      .all(|x| x.runway != a.runway && x.runway != b.runway);
+    println!(
+      "Conflict: #{} -> #{} by {}{}",
+      a.plane_id,
+      b.plane_id,
+      duration,
+      if adjacent { "" } else { " (non-adjacent)" }
+    );
+  }
 }
 
 fn run_sa(
@@ -66,6 +102,7 @@ fn run_sa(
   alpha: f64,
   initial_temp: f64,
   bounded: bool,
+  rng: &mut StdRng,
 ) -> (Solution, Duration) {
   let n = problem.planes.len();
   let max_iterations = if bounded { 10 * n * n } else { usize::MAX };
@@ -77,137 +114,1697 @@ fn run_sa(
     alpha,
     (sa_max_k * n as f64) as usize,
     initial_temp,
+    rng,
   );
   (solution, start.elapsed())
 }
 
-fn main() {
-  let args: Vec<String> = args().collect();
-  match &args[..] {
-    [_, run_type, sa_max_k, alpha, initial_temp, tail @ ..] => {
-      let sa_max_k = sa_max_k.parse::<f64>().unwrap();
-      let alpha = alpha.parse::<f64>().unwrap();
-      let initial_temp = initial_temp.parse::<f64>().unwrap();
-      match run_type.as_str() {
-        "irace" => {
-          let problem = LandingProblem::from_parser(parse_problem_data(&tail[0]).unwrap());
-          let (solution, duration) = run_sa(
-            &problem,
-            &problem.initial_solution(),
-            sa_max_k,
-            alpha,
-            initial_temp,
-            true,
-          );
-          println!("{}", problem.cost(&solution));
-          println!("{}", duration.as_secs_f64());
+fn run_tabu(
+  problem: &LandingProblem,
+  solution: &Solution,
+  tenure: usize,
+  candidates: usize,
+  rng: &mut StdRng,
+) -> (Solution, Duration) {
+  let n = problem.planes.len();
+  // Evaluates as many moves as a bounded SA run
+  let max_iterations = 10 * n * n / candidates;
+  let start = time::Instant::now();
+  let solution = tabu_search(problem, solution, max_iterations, tenure, candidates, rng);
+  (solution, start.elapsed())
+}
+
+/// Best solutions the GA keeps from a generation to the next
+const GA_ELITES: usize = 2;
+
+fn run_ga(
+  problem: &LandingProblem,
+  solution: &Solution,
+  population: usize,
+  rng: &mut StdRng,
+) -> (Solution, Duration) {
+  let parameters = GeneticParameters {
+    population,
+    generations: 10 * problem.planes.len(),
+    tournament: 3,
+    elites: GA_ELITES,
+    mutation_rate: 0.2,
+  };
+  let start = time::Instant::now();
+  let solution = genetic_algorithm(problem, solution, &Crossover::ALL, parameters, rng);
+  (solution, start.elapsed())
+}
+
+fn run_vns(
+  problem: &LandingProblem,
+  solution: &Solution,
+  rng: &mut StdRng,
+) -> (Solution, Duration) {
+  // Every iteration runs a whole descent, already longer on larger instances
+  let max_iterations = 100;
+  let start = time::Instant::now();
+  let solution = vns(problem, solution, max_iterations, rng);
+  (solution, start.elapsed())
+}
+
+fn run_ils(
+  problem: &LandingProblem,
+  solution: &Solution,
+  ils_gas: usize,
+  climb_gas: Option<usize>,
+  rng: &mut StdRng,
+) -> (Solution, Duration) {
+  let climb_gas = climb_gas.unwrap_or(problem.planes.len());
+  let start = time::Instant::now();
+  let solution = ils_from(problem, solution.clone(), ils_gas, climb_gas, rng);
+  (solution, start.elapsed())
+}
+
+fn run_hill_climbing(
+  problem: &LandingProblem,
+  solution: &Solution,
+  gas: Option<usize>,
+  rng: &mut StdRng,
+) -> (Solution, Duration) {
+  let gas = gas.unwrap_or(problem.planes.len());
+  let start = time::Instant::now();
+  let solution = hill_climb(problem, solution.clone(), gas, rng);
+  (solution, start.elapsed())
+}
+
+/// Best SA configuration irace found on the instances of `tuning/`
+fn default_sa_max_k() -> f64 {
+  3.8272
+}
+
+fn default_alpha() -> f64 {
+  0.9809
+}
+
+fn default_initial_temp() -> f64 {
+  10599.1518
+}
+
+fn default_tenure() -> usize {
+  20
+}
+
+fn default_candidates() -> usize {
+  100
+}
+
+fn default_population() -> usize {
+  50
+}
+
+/// Perturbations without improvement before ILS stops, like VNS iterations
+fn default_ils_gas() -> usize {
+  100
+}
+
+fn default_rcl() -> f64 {
+  0.3
+}
+
+/// In configuration files, a table with the name of the algorithm and its
+/// parameters, e.g. `{ name = "ils", ils_gas = 50 }`. Climbs default to as
+/// many steps as planes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "name", deny_unknown_fields)]
+enum Algorithm {
+  #[serde(rename = "sa")]
+  SimulatedAnnealing {
+    #[serde(default = "default_sa_max_k")]
+    sa_max_k: f64,
+    #[serde(default = "default_alpha")]
+    alpha: f64,
+    #[serde(default = "default_initial_temp")]
+    initial_temp: f64,
+  },
+  #[serde(rename = "tabu")]
+  Tabu {
+    #[serde(default = "default_tenure")]
+    tenure: usize,
+    #[serde(default = "default_candidates")]
+    candidates: usize,
+  },
+  #[serde(rename = "ga")]
+  Genetic {
+    #[serde(default = "default_population")]
+    population: usize,
+  },
+  #[serde(rename = "vns")]
+  VariableNeighborhood,
+  #[serde(rename = "ils")]
+  IteratedLocalSearch {
+    #[serde(default = "default_ils_gas")]
+    ils_gas: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    climb_gas: Option<usize>,
+  },
+  #[serde(rename = "hc")]
+  HillClimbing {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    climb_gas: Option<usize>,
+  },
+}
+
+impl Default for Algorithm {
+  fn default() -> Self {
+    Algorithm::named("sa").unwrap()
+  }
+}
+
+impl Algorithm {
+  /// The algorithm `name` with its default parameters
+  fn named(name: &str) -> Result<Self, UsageError> {
+    match name {
+      "sa" => Ok(Algorithm::SimulatedAnnealing {
+        sa_max_k: default_sa_max_k(),
+        alpha: default_alpha(),
+        initial_temp: default_initial_temp(),
+      }),
+      "tabu" => Ok(Algorithm::Tabu {
+        tenure: default_tenure(),
+        candidates: default_candidates(),
+      }),
+      "ga" => Ok(Algorithm::Genetic {
+        population: default_population(),
+      }),
+      "vns" => Ok(Algorithm::VariableNeighborhood),
+      "ils" => Ok(Algorithm::IteratedLocalSearch {
+        ils_gas: default_ils_gas(),
+        climb_gas: None,
+      }),
+      "hc" => Ok(Algorithm::HillClimbing { climb_gas: None }),
+      _ => Err(UsageError(format!(
+        "unknown algorithm {}, expected sa, tabu, ga, vns, ils or hc",
+        name
+      ))),
+    }
+  }
+
+  /// Takes `--algorithm <name>`, which replaces `self`, and the parameters,
+  /// which override those of the algorithm and are ignored by the others
+  fn take(self, args: &mut Arguments) -> Result<Self, UsageError> {
+    let sa_max_k = args.value("--sa-max-k")?;
+    let alpha = args.value("--alpha")?;
+    let initial_temp = args.value("--initial-temp")?;
+    let tenure = args.value("--tenure")?;
+    let candidates = args.value("--candidates")?;
+    let population = args.value("--population")?;
+    let ils_gas = args.value("--ils-gas")?;
+    let climb_gas = args.value("--climb-gas")?;
+    // Naming the algorithm of `self` keeps its parameters
+    let mut algorithm = match args.option("--algorithm")? {
+      Some(name) => {
+        let named = Algorithm::named(&name)?;
+        if mem::discriminant(&named) == mem::discriminant(&self) {
+          self
+        } else {
+          named
         }
-        "eval-one" => {
-          let [file_path, max_time] = tail else {
-            panic!("Pass in file_path and max_time")
-          };
-          let max_time = max_time.parse::<f64>().unwrap();
-          let problem = LandingProblem::from_parser(parse_problem_data(file_path).unwrap());
-          let mut solution = problem.initial_solution();
-          let mut duration = Duration::ZERO;
-          display_solution(&problem, &solution);
-          while duration.as_secs_f64() < max_time {
-            let result = run_sa(&problem, &solution, sa_max_k, alpha, initial_temp, false);
-            solution = result.0;
-            duration += result.1;
-
-            for _ in 0..10 {
-              solution = problem.first_improvement_neighbor(&solution);
-            }
-          }
-          display_solution(&problem, &solution);
+      }
+      None => self,
+    };
+    match &mut algorithm {
+      Algorithm::SimulatedAnnealing {
+        sa_max_k: k,
+        alpha: a,
+        initial_temp: t,
+      } => {
+        *k = sa_max_k.unwrap_or(*k);
+        *a = alpha.unwrap_or(*a);
+        *t = initial_temp.unwrap_or(*t);
+      }
+      Algorithm::Tabu {
+        tenure: t,
+        candidates: c,
+      } => {
+        *t = tenure.unwrap_or(*t);
+        *c = candidates.unwrap_or(*c);
+      }
+      Algorithm::Genetic { population: p } => *p = population.unwrap_or(*p),
+      Algorithm::VariableNeighborhood => {}
+      Algorithm::IteratedLocalSearch {
+        ils_gas: i,
+        climb_gas: c,
+      } => {
+        *i = ils_gas.unwrap_or(*i);
+        *c = climb_gas.or(*c);
+      }
+      Algorithm::HillClimbing { climb_gas: c } => *c = climb_gas.or(*c),
+    }
+    algorithm.check()?;
+    Ok(algorithm)
+  }
+
+  /// Fails on the parameters the algorithm cannot run with, whether they come
+  /// from the command line or from a configuration file
+  fn check(&self) -> Result<(), UsageError> {
+    match *self {
+      Algorithm::SimulatedAnnealing {
+        sa_max_k,
+        alpha,
+        initial_temp,
+      } => {
+        positive("--sa-max-k", sa_max_k)?;
+        positive("--initial-temp", initial_temp)?;
+        if !(alpha > 0.0 && alpha < 1.0) {
+          return Err(UsageError(
+            "--alpha must be between 0 and 1, exclusive".to_string(),
+          ));
+        }
+        Ok(())
+      }
+      Algorithm::Tabu { candidates, .. } => at_least("--candidates", candidates, 1),
+      Algorithm::Genetic { population } => at_least("--population", population, GA_ELITES + 1),
+      _ => Ok(()),
+    }
+  }
+
+  /// Sets the SA parameters, if this is SA
+  fn set_sa(&mut self, k: f64, a: f64, t: f64) {
+    if let Algorithm::SimulatedAnnealing {
+      sa_max_k,
+      alpha,
+      initial_temp,
+    } = self
+    {
+      (*sa_max_k, *alpha, *initial_temp) = (k, a, t);
+    }
+  }
+
+  /// Runs the algorithm once from `solution`. Only SA can run unbounded, the
+  /// other algorithms always stop on their own
+  fn run(
+    self,
+    problem: &LandingProblem,
+    solution: &Solution,
+    bounded: bool,
+    rng: &mut StdRng,
+  ) -> (Solution, Duration) {
+    match self {
+      Algorithm::SimulatedAnnealing {
+        sa_max_k,
+        alpha,
+        initial_temp,
+      } => run_sa(
+        problem,
+        solution,
+        sa_max_k,
+        alpha,
+        initial_temp,
+        bounded,
+        rng,
+      ),
+      Algorithm::Tabu { tenure, candidates } => {
+        run_tabu(problem, solution, tenure, candidates, rng)
+      }
+      Algorithm::Genetic { population } => run_ga(problem, solution, population, rng),
+      Algorithm::VariableNeighborhood => run_vns(problem, solution, rng),
+      Algorithm::IteratedLocalSearch { ils_gas, climb_gas } => {
+        run_ils(problem, solution, ils_gas, climb_gas, rng)
+      }
+      Algorithm::HillClimbing { climb_gas } => run_hill_climbing(problem, solution, climb_gas, rng),
+    }
+  }
+}
+
+/// How runs get their first solution. In configuration files, a table with
+/// its name and parameters, e.g. `{ name = "construct", heuristic = "edf" }`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "lowercase", deny_unknown_fields)]
+enum Start {
+  #[default]
+  Initial,
+  /// Best of several GRASP constructions
+  Grasp {
+    iterations: usize,
+    #[serde(rename = "rcl", default = "default_rcl")]
+    alpha: f64,
+  },
+  /// A deterministic constructive heuristic, or the initial solution if it
+  /// fails
+  #[serde(rename = "construct")]
+  Constructed { heuristic: Heuristic },
+}
+
+impl Start {
+  /// Takes `--construct <name>` or `--grasp <iterations>`, which replace
+  /// `self`, and `--rcl <alpha>`
+  fn take(self, args: &mut Arguments) -> Result<Self, UsageError> {
+    let alpha = args.value("--rcl")?;
+    let iterations = args.value("--grasp")?;
+    let heuristic = args.value("--construct")?;
+    let start = match (heuristic, iterations, self) {
+      (Some(heuristic), _, _) => Start::Constructed { heuristic },
+      (None, Some(iterations), Start::Grasp { alpha, .. }) => Start::Grasp { iterations, alpha },
+      (None, Some(iterations), _) => Start::Grasp {
+        iterations,
+        alpha: default_rcl(),
+      },
+      (None, None, start) => start,
+    };
+    let start = match start {
+      Start::Grasp {
+        iterations,
+        alpha: rcl,
+      } => Start::Grasp {
+        iterations,
+        alpha: alpha.unwrap_or(rcl),
+      },
+      start => start,
+    };
+    start.check()?;
+    Ok(start)
+  }
+
+  fn check(&self) -> Result<(), UsageError> {
+    match *self {
+      Start::Grasp { iterations, alpha } => {
+        at_least("--grasp", iterations, 1)?;
+        if !(0.0..=1.0).contains(&alpha) {
+          return Err(UsageError("--rcl must be between 0 and 1".to_string()));
         }
-        "eval-all" => {
-          let [folder_path, max_time] = tail else {
-            panic!("Pass in folder_path max_time")
-          };
-          let max_time = max_time.parse::<f64>().unwrap();
-
-          let mut files = std::fs::read_dir(folder_path)
-            .unwrap()
-            .try_collect::<Vec<_>>()
-            .unwrap();
-          files.sort_by_key(|f| {
-            f.file_name()
-              .into_string()
-              .unwrap()
-              .chars()
-              .filter(|char| char.is_ascii_digit())
-              .collect::<String>()
-              .parse::<i32>()
-              .unwrap()
-          });
-          let solutions = files.into_iter().map(|file| {
-            let file_path = file.path().to_string_lossy().to_string();
-            let problem = LandingProblem::from_parser(parse_problem_data(&file_path).unwrap());
-
-            let mut solution = problem.initial_solution();
-            let mut duration = Duration::ZERO;
-            while duration.as_secs_f64() < max_time {
-              let result = run_sa(&problem, &solution, sa_max_k, alpha, initial_temp, false);
-              solution = result.0;
-              duration += result.1;
-              for _ in 0..10 {
-                solution = problem.first_improvement_neighbor(&solution);
-              }
-            }
-            (file, problem, solution)
-          });
-
-          for (file, problem, solution) in solutions {
-            println!(
-              "{}\t{}\t{}\t{}",
-              file.file_name().into_string().unwrap(),
-              problem.planes.len(),
-              problem.landing_cost(&solution) as u64,
-              problem.is_valid(&solution)
-            );
-          }
+        Ok(())
+      }
+      _ => Ok(()),
+    }
+  }
+
+  fn solution(self, problem: &LandingProblem, rng: &mut StdRng) -> Solution {
+    match self {
+      Start::Initial => problem.initial_solution(),
+      Start::Grasp { iterations, alpha } => {
+        let climb_gas = problem.planes.len();
+        let local_search = |s, rng: &mut StdRng| hill_climb(problem, s, climb_gas, rng);
+        grasp(problem, iterations, alpha, local_search, rng)
+      }
+      Start::Constructed { heuristic } => match problem.construct(heuristic) {
+        Ok(solution) => solution,
+        Err(e) => {
+          eprintln!("{}: {}", heuristic.name(), e);
+          problem.initial_solution()
         }
-        _ => panic!("Usage: cargo run <run_type> <sa_max_k> <alpha> <initial_temp> ..."),
-      }
-    }
-    _ => panic!("Usage: cargo run <run_type> <sa_max_k> <alpha> <initial_temp> ..."),
-  }
-
-  // let problem = LandingProblem::from_parser(parse_problem_data(file_path).unwrap());
-  // let n = problem.planes.len();
-  // let mut solution = problem.initial_solution();
-
-  // let max_iterations = if unbounded { usize::MAX } else { n * n * 100 };
-  // let mut duration = Duration::ZERO;
-  // loop {
-  //   let before = time::Instant::now();
-  //   solution = simulated_annealing(
-  //     &problem,
-  //     &solution,
-  //     max_iterations,
-  //     alpha,
-  //     (sa_max_k * n as f64) as usize,
-  //     initial_temp,
-  //   );
-  //   let after = time::Instant::now();
-  //   duration += after.duration_since(before);
-  //   if !unbounded || duration.as_secs_f32() > 2.0 {
-  //     break;
-  //   }
-  // }
-  // display_solution(&problem, &solution);
-  // if !unbounded {
-  //   println!("{}", problem.cost(&solution));
-  //   println!("{}", duration.as_secs_f32());
-  // } else {
-  //   println!("Further improvements");
-  //   for _ in 0..1000 {
-  //     solution = problem.first_improvement_neighbor(&solution);
-  //   }
-  //   display_solution(&problem, &solution);
-  // }
+      },
+    }
+  }
+}
+
+/// An algorithm with its parameters and start, as compared or tuned
+#[derive(Debug, Clone)]
+struct Configuration {
+  /// As written on the command line
+  label: String,
+  algorithm: Algorithm,
+  start: Start,
+}
+
+impl Configuration {
+  /// Takes the algorithm, its parameters and the start
+  fn take(args: &mut Arguments) -> Result<Self, UsageError> {
+    Ok(Configuration {
+      label: String::new(),
+      algorithm: Algorithm::default().take(args)?,
+      start: Start::default().take(args)?,
+    })
+  }
+
+  /// Takes the SA parameters as positional arguments, in the order of the
+  /// irace command
+  fn take_sa_positionals(&mut self, args: &mut Arguments) -> Result<(), UsageError> {
+    let sa_max_k = args.positional("sa_max_k")?;
+    let alpha = args.positional("alpha")?;
+    let initial_temp = args.positional("initial_temp")?;
+    self.algorithm.set_sa(sa_max_k, alpha, initial_temp);
+    self.algorithm.check()
+  }
+
+  /// Parses solver options, possibly after the SA parameters in the order of
+  /// the irace command, e.g. `"0.5 0.95 1000 --algorithm ils --construct edf"`
+  fn parse(label: &str) -> Result<Self, UsageError> {
+    let mut args = Arguments(label.split_whitespace().map(String::from).collect());
+    let mut configuration = Configuration::take(&mut args)?;
+    if !args.0.is_empty() {
+      configuration.take_sa_positionals(&mut args)?;
+    }
+    args.finish()?;
+    configuration.label = label.to_string();
+    Ok(configuration)
+  }
+
+  /// A single bounded run from the start, and its time with the construction
+  fn run_bounded(&self, problem: &LandingProblem, rng: &mut StdRng) -> (Solution, Duration) {
+    let before = time::Instant::now();
+    let solution = self.start.solution(problem, rng);
+    let construction = before.elapsed();
+    let (solution, duration) = self.algorithm.run(problem, &solution, true, rng);
+    (solution, construction + duration)
+  }
+
+  /// Reruns the algorithm from its last solution until `max_time` seconds are
+  /// spent in it. Returns the best solution and the time it took to find it
+  fn solve(
+    &self,
+    problem: &LandingProblem,
+    max_time: f64,
+    rng: &mut StdRng,
+  ) -> (Solution, Duration) {
+    let before = time::Instant::now();
+    let mut solution = self.start.solution(problem, rng);
+    let mut best = (solution.clone(), before.elapsed());
+    let mut duration = Duration::ZERO;
+    while duration.as_secs_f64() < max_time {
+      let result = self.algorithm.run(problem, &solution, false, rng);
+      solution = result.0;
+      duration += result.1;
+      for _ in 0..10 {
+        solution = problem.first_improvement_neighbor(&solution, rng);
+      }
+      if problem.cost(&solution) < problem.cost(&best.0) {
+        best = (solution.clone(), before.elapsed());
+      }
+    }
+    best
+  }
+}
+
+fn load_problem(file_path: &str, mode: ParseMode) -> LandingProblem {
+  match parse_problem_data_with(file_path, mode) {
+    Ok(data) => {
+      warn_replaced(file_path, &data.replaced);
+      LandingProblem::from_parser(data)
+    }
+    Err(e) => {
+      eprintln!("{}: {}", file_path, e);
+      process::exit(1);
+    }
+  }
+}
+
+fn warn_replaced(file_path: &str, replaced: &[ParseError]) {
+  for e in replaced {
+    eprintln!("{}: warning: {}, read as 0", file_path, e);
+  }
+  if !replaced.is_empty() {
+    eprintln!(
+      "{}: warning: {} malformed separation times read as 0, rejected with --strict",
+      file_path,
+      replaced.len()
+    );
+  }
+}
+
+/// `problem` with the separation times between runways of `file_path`, if
+/// any, otherwise runways stay independent
+fn with_cross_separation(
+  problem: LandingProblem,
+  file_path: Option<&str>,
+  mode: ParseMode,
+) -> LandingProblem {
+  let Some(file_path) = file_path else {
+    return problem;
+  };
+  match parse_separation_matrix_from(file_path, problem.planes.len(), mode) {
+    Ok(matrix) => {
+      warn_replaced(file_path, &matrix.replaced);
+      problem.with_cross_runway_separation(matrix.separation_times)
+    }
+    Err(e) => {
+      eprintln!("{}: {}", file_path, e);
+      process::exit(1);
+    }
+  }
+}
+
+/// The instances of a folder, by number, on `runways`
+fn load_folder(
+  folder_path: &str,
+  mode: ParseMode,
+  runways: usize,
+) -> Vec<(String, LandingProblem)> {
+  let files = std::fs::read_dir(folder_path).and_then(|mut files| files.try_collect::<Vec<_>>());
+  let mut files = match files {
+    Ok(files) => files,
+    Err(e) => {
+      eprintln!("{}: {}", folder_path, e);
+      process::exit(1);
+    }
+  };
+  files.sort_by_key(|f| {
+    f.file_name()
+      .into_string()
+      .unwrap()
+      .chars()
+      .filter(|char| char.is_ascii_digit())
+      .collect::<String>()
+      .parse::<i32>()
+      .ok()
+  });
+  files
+    .into_iter()
+    .map(|file| {
+      let file_path = file.path().to_string_lossy().to_string();
+      let problem = load_problem(&file_path, mode).with_runways(runways);
+      (file.file_name().into_string().unwrap(), problem)
+    })
+    .collect()
+}
+
+/// Options every command accepts
+struct Common {
+  seed: Option<u64>,
+  mode: ParseMode,
+}
+
+impl Common {
+  fn take(args: &mut Arguments) -> Result<Self, UsageError> {
+    let seed = args.value("--seed")?;
+    // Rejects malformed separation times instead of reading them as 0, and
+    // targets outside of landing windows
+    let mode = if args.flag("--strict") {
+      ParseMode::Strict
+    } else {
+      ParseMode::Lenient
+    };
+    Ok(Common { seed, mode })
+  }
+}
+
+/// `seed`, or a random one, reported so that the run can be repeated. The
+/// same seed, instance and parameters give the same schedule, except in the
+/// time limited commands, where the number of runs depends on timing, as
+/// `--help` warns
+fn resolve_seed(seed: Option<u64>) -> u64 {
+  let seed = seed.unwrap_or_else(rand::random);
+  eprintln!("Seed={}", seed);
+  seed
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OutputFormat {
+  /// Every arrival, with its cost and separations
+  #[default]
+  Table,
+  /// Only the costs
+  Summary,
+  /// A `SolutionReport` per line
+  Json,
+  /// The rows of every `SolutionReport`, after a single header
+  Csv,
+}
+
+impl FromStr for OutputFormat {
+  type Err = String;
+
+  fn from_str(name: &str) -> Result<Self, Self::Err> {
+    match name {
+      "table" => Ok(OutputFormat::Table),
+      "summary" => Ok(OutputFormat::Summary),
+      "json" => Ok(OutputFormat::Json),
+      "csv" => Ok(OutputFormat::Csv),
+      _ => Err("expected table, summary, json or csv".to_string()),
+    }
+  }
+}
+
+/// A complete run of the solve command, as read from a TOML or JSON file and
+/// echoed before the results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RunConfig {
+  instances: Vec<String>,
+  runways: usize,
+  /// Separation times between runways, as read by `--cross-separation`
+  #[serde(skip_serializing_if = "Option::is_none")]
+  cross_separation: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  seed: Option<u64>,
+  /// Seconds spent on every instance
+  time: f64,
+  /// Whether to run the algorithm once, bounded, instead of until `time`
+  bounded: bool,
+  format: OutputFormat,
+  algorithm: Algorithm,
+  start: Start,
+}
+
+impl Default for RunConfig {
+  fn default() -> Self {
+    RunConfig {
+      instances: Vec::new(),
+      runways: 1,
+      cross_separation: None,
+      seed: None,
+      time: 1.0,
+      bounded: false,
+      format: OutputFormat::default(),
+      algorithm: Algorithm::default(),
+      start: Start::default(),
+    }
+  }
+}
+
+impl RunConfig {
+  /// Reads JSON from `.json` files and TOML from the others
+  fn read_from(file_path: &str) -> Result<Self, ConfigError> {
+    let text = std::fs::read_to_string(file_path)?;
+    Self::parse(&text, file_path.ends_with(".json"))
+  }
+
+  fn parse(text: &str, json: bool) -> Result<Self, ConfigError> {
+    if json {
+      Ok(serde_json::from_str(text)?)
+    } else {
+      Ok(toml::from_str(text)?)
+    }
+  }
+}
+
+#[derive(Debug)]
+enum ConfigError {
+  Io(io::Error),
+  Toml(toml::de::Error),
+  Json(serde_json::Error),
+}
+
+impl fmt::Display for ConfigError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ConfigError::Io(e) => write!(f, "I/O error: {}", e),
+      ConfigError::Toml(e) => write!(f, "{}", e),
+      ConfigError::Json(e) => write!(f, "{}", e),
+    }
+  }
+}
+
+impl error::Error for ConfigError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      ConfigError::Io(e) => Some(e),
+      ConfigError::Toml(e) => Some(e),
+      ConfigError::Json(e) => Some(e),
+    }
+  }
+}
+
+impl From<io::Error> for ConfigError {
+  fn from(e: io::Error) -> Self {
+    ConfigError::Io(e)
+  }
+}
+
+impl From<toml::de::Error> for ConfigError {
+  fn from(e: toml::de::Error) -> Self {
+    ConfigError::Toml(e)
+  }
+}
+
+impl From<serde_json::Error> for ConfigError {
+  fn from(e: serde_json::Error) -> Self {
+    ConfigError::Json(e)
+  }
+}
+
+/// Solves the instances of a configuration file, if any, with its fields
+/// overridden by the options given
+fn solve(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let mut run = match args.option("--config-file")? {
+    None => RunConfig::default(),
+    Some(file_path) => RunConfig::read_from(&file_path).unwrap_or_else(|e| {
+      eprintln!("{}: {}", file_path, e);
+      process::exit(1);
+    }),
+  };
+  run.algorithm = run.algorithm.take(args)?;
+  run.start = run.start.take(args)?;
+  run.time = args.value("--time")?.unwrap_or(run.time);
+  run.bounded |= args.flag("--bounded");
+  run.runways = args.count("--runways", 1)?.unwrap_or(run.runways);
+  if let Some(file_path) = args.option("--cross-separation")? {
+    run.cross_separation = Some(file_path);
+  }
+  run.format = args.value("--format")?.unwrap_or(run.format);
+  let instances: Vec<String> = args.positionals("instance")?;
+  if !instances.is_empty() {
+    run.instances = instances;
+  }
+  args.finish()?;
+  at_least("--runways", run.runways, 1)?;
+  if run.instances.is_empty() {
+    return Err(UsageError("missing <instance>".to_string()));
+  }
+  let seed = resolve_seed(common.seed.or(run.seed));
+  run.seed = Some(seed);
+  // Machine-readable output only holds the reports, so the configuration goes
+  // to stderr with them
+  let machine_readable = matches!(run.format, OutputFormat::Json | OutputFormat::Csv);
+  for line in toml::to_string(&run).unwrap().lines() {
+    let line = format!("# {}", line);
+    if machine_readable {
+      eprintln!("{}", line.trim_end());
+    } else {
+      println!("{}", line.trim_end());
+    }
+  }
+
+  let configuration = Configuration {
+    label: String::new(),
+    algorithm: run.algorithm,
+    start: run.start,
+  };
+  let mut stdout = io::stdout().lock();
+  if run.format == OutputFormat::Csv {
+    SolutionReport::write_csv_header(&mut stdout).unwrap();
+  }
+  for file_path in &run.instances {
+    let problem = load_problem(file_path, common.mode).with_runways(run.runways);
+    let problem = with_cross_separation(problem, run.cross_separation.as_deref(), common.mode);
+    // Every instance gets the seed, as when solved alone
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (solution, duration) = if run.bounded {
+      configuration.run_bounded(&problem, &mut rng)
+    } else {
+      configuration.solve(&problem, run.time, &mut rng)
+    };
+    let report = || SolutionReport::new(file_path, &problem, &solution, duration, seed);
+    match run.format {
+      OutputFormat::Table | OutputFormat::Summary => {
+        println!("Instance={}", file_path);
+        if run.format == OutputFormat::Table {
+          display_solution(&problem, &solution);
+        } else {
+          display_costs(&problem, &solution);
+        }
+        println!("Time={}", duration.as_secs_f64());
+      }
+      OutputFormat::Json => report().write_json(&mut stdout).unwrap(),
+      OutputFormat::Csv => report().write_csv(&mut stdout).unwrap(),
+    }
+  }
+  Ok(())
+}
+
+fn eval_all(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let configuration = Configuration::take(args)?;
+  let max_time = args.value("--time")?.unwrap_or(1.0);
+  let bounded = args.flag("--bounded");
+  let runways = args.count("--runways", 1)?.unwrap_or(1);
+  let runs = args.count("--runs", 1)?.unwrap_or(1);
+  let workers = args
+    .count("--workers", 1)?
+    .unwrap_or_else(available_workers);
+  let best_known_path: Option<String> = args.option("--best-known")?;
+  let folder_path: String = args.positional("folder")?;
+  args.finish()?;
+  let mut best_known = BestKnownTable::builtin();
+  if let Some(file_path) = &best_known_path {
+    if let Err(e) = best_known.read_overrides_from(file_path) {
+      eprintln!("{}: {}", file_path, e);
+      process::exit(1);
+    }
+  }
+  let problems = load_folder(&folder_path, common.mode, runways);
+  let seed = resolve_seed(common.seed);
+
+  // Job `k` is run `k % runs` of instance `k / runs`, seeded from the seed and
+  // `k` only
+  let results = run_parallel(problems.len() * runs, workers, |k| {
+    let problem = &problems[k / runs].1;
+    let mut rng = StdRng::seed_from_u64(job_seed(seed, k as u64));
+    if bounded {
+      configuration.run_bounded(problem, &mut rng)
+    } else {
+      configuration.solve(problem, max_time, &mut rng)
+    }
+  });
+
+  // `best` and `mean`, `worst` and `std_dev` are landing costs of the valid
+  // runs, `invalid` counting the others
+  println!(concat!(
+    "instance\tplanes\tbest\tvalid\tbest_known\tgap\ttime\t",
+    "runs\tinvalid\tmean\tworst\tstd_dev"
+  ));
+  for ((file_name, problem), runs) in problems.iter().zip(results.chunks(runs)) {
+    // Landing costs of the valid runs only, as conflicts let invalid ones
+    // undercut any cost
+    let valid_runs: Vec<&(Solution, Duration)> =
+      runs.iter().filter(|(s, _)| problem.is_valid(s)).collect();
+    let costs: Vec<f64> = valid_runs
+      .iter()
+      .map(|(s, _)| problem.landing_cost(s))
+      .collect();
+    let by_cost = |(a, _): &&(Solution, Duration), (b, _): &&(Solution, Duration)| {
+      problem.cost(a).partial_cmp(&problem.cost(b)).unwrap()
+    };
+    // Without valid runs, the one with the fewest conflicts
+    let (solution, time_to_best) = match valid_runs.iter().copied().min_by(by_cost) {
+      Some(best) => best,
+      None => runs.iter().min_by(by_cost).unwrap(),
+    };
+    let valid = !valid_runs.is_empty();
+    let summary = valid.then(|| Summary::of(&costs));
+    let cost = summary.map_or_else(|| problem.landing_cost(solution), |s| s.best);
+    let (best_known, gap) = match best_known.get(file_name, problem.runways) {
+      Some(best) if valid => (
+        best.cost.to_string(),
+        format!("{:.2}", gap(cost, best.cost)),
+      ),
+      Some(best) => (best.cost.to_string(), "-".to_string()),
+      None => ("-".to_string(), "-".to_string()),
+    };
+    let statistics = match summary {
+      Some(s) => format!("{:.2}\t{:.2}\t{:.2}", s.mean, s.worst, s.std_dev),
+      None => "-\t-\t-".to_string(),
+    };
+    println!(
+      "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+      file_name,
+      problem.planes.len(),
+      cost,
+      valid,
+      best_known,
+      gap,
+      time_to_best.as_secs_f64(),
+      runs.len(),
+      runs.len() - valid_runs.len(),
+      statistics
+    );
+  }
+  Ok(())
+}
+
+/// The command `tuning/target-runner` calls, with the SA parameters in the
+/// order of `tuning/parameters.txt`. Prints the cost, then the time
+fn irace(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let mut configuration = Configuration::take(args)?;
+  configuration.take_sa_positionals(args)?;
+  let file_path: String = args.positional("instance")?;
+  args.finish()?;
+  let problem = load_problem(&file_path, common.mode);
+  let mut rng = StdRng::seed_from_u64(resolve_seed(common.seed));
+  let (solution, duration) = configuration.run_bounded(&problem, &mut rng);
+  println!("{}", problem.cost(&solution));
+  println!("{}", duration.as_secs_f64());
+  Ok(())
+}
+
+fn simulate_command(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let configuration = Configuration::take(args)?;
+  let runways = args.count("--runways", 1)?.unwrap_or(1);
+  let cross_separation = args.option("--cross-separation")?;
+  let file_path: String = args.positional("instance")?;
+  args.finish()?;
+  let problem = load_problem(&file_path, common.mode).with_runways(runways);
+  let mut problem = with_cross_separation(problem, cross_separation.as_deref(), common.mode);
+  let mut rng = StdRng::seed_from_u64(resolve_seed(common.seed));
+  let report = simulate(&mut problem, |problem, start| {
+    let (solution, _) = configuration.algorithm.run(problem, start, true, &mut rng);
+    solution
+  });
+  display_solution(&problem, &report.schedule);
+  println!(
+    "Events={}\tReschedules={}\tDisplacement={}",
+    report.events,
+    report.reschedules.iter().sum::<usize>(),
+    report.total_displacement
+  );
+  for (id, reschedules) in report.reschedules.iter().enumerate() {
+    println!("#{}\t{}", id, reschedules);
+  }
+  Ok(())
+}
+
+fn baselines(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let runways = args.count("--runways", 1)?.unwrap_or(1);
+  let cross_separation = args.option("--cross-separation")?;
+  let file_path: String = args.positional("instance")?;
+  args.finish()?;
+  let problem = load_problem(&file_path, common.mode).with_runways(runways);
+  let problem = with_cross_separation(problem, cross_separation.as_deref(), common.mode);
+  for heuristic in Heuristic::ALL {
+    let before = time::Instant::now();
+    match problem.construct(heuristic) {
+      Ok(solution) => println!(
+        "{}\t{}\t{}\t{}",
+        heuristic.name(),
+        problem.landing_cost(&solution),
+        problem.is_valid(&solution),
+        before.elapsed().as_secs_f64()
+      ),
+      Err(e) => println!("{}\t{}", heuristic.name(), e),
+    }
+  }
+  Ok(())
+}
+
+fn exact(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let start = Start::default().take(args)?;
+  let max_time = args.value("--time")?.unwrap_or(60.0);
+  let max_nodes = args.value("--nodes")?.unwrap_or(usize::MAX);
+  let runways = args.count("--runways", 1)?.unwrap_or(1);
+  let cross_separation = args.option("--cross-separation")?;
+  let file_path: String = args.positional("instance")?;
+  args.finish()?;
+  let problem = load_problem(&file_path, common.mode).with_runways(runways);
+  let problem = with_cross_separation(problem, cross_separation.as_deref(), common.mode);
+  let mut rng = StdRng::seed_from_u64(resolve_seed(common.seed));
+  let incumbent = start.solution(&problem, &mut rng);
+  let limits = BranchAndBoundLimits {
+    nodes: max_nodes,
+    time: Duration::from_secs_f64(max_time),
+  };
+  let exact = branch_and_bound(&problem, Some(&incumbent), limits);
+  if let Some(solution) = &exact.solution {
+    display_solution(&problem, solution);
+  }
+  println!(
+    "Cost={}\tBound={}\tGap={:.2}%\tOptimal={}\tNodes={}\tTime={}",
+    exact.cost,
+    exact.bound,
+    100.0 * exact.gap(),
+    exact.optimal,
+    exact.nodes,
+    exact.duration.as_secs_f64()
+  );
+  Ok(())
+}
+
+fn mip(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let runways = args.count("--runways", 1)?.unwrap_or(1);
+  let cross_separation = args.option("--cross-separation")?;
+  let file_path: String = args.positional("instance")?;
+  let output: String = args.positional("output")?;
+  args.finish()?;
+  let problem = load_problem(&file_path, common.mode).with_runways(runways);
+  let problem = with_cross_separation(problem, cross_separation.as_deref(), common.mode);
+  let model = problem.mip_model();
+  let written = File::create(&output).and_then(|file| {
+    let mut writer = io::BufWriter::new(file);
+    if output.ends_with(".mps") {
+      model.write_mps(&mut writer)?;
+    } else {
+      model.write_lp(&mut writer)?;
+    }
+    writer.flush()
+  });
+  if let Err(e) = written {
+    eprintln!("{}: {}", output, e);
+    process::exit(1);
+  }
+  println!(
+    "Variables={}\tConstraints={}",
+    model.variables.len(),
+    model.constraints.len()
+  );
+  Ok(())
+}
+
+fn mip_solution(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let runways = args.count("--runways", 1)?.unwrap_or(1);
+  let cross_separation = args.option("--cross-separation")?;
+  let file_path: String = args.positional("instance")?;
+  let solution_path: String = args.positional("solution")?;
+  args.finish()?;
+  let problem = load_problem(&file_path, common.mode).with_runways(runways);
+  let problem = with_cross_separation(problem, cross_separation.as_deref(), common.mode);
+  let solution = File::open(&solution_path)
+    .map_err(SolutionError::from)
+    .and_then(|file| problem.read_mip_solution(io::BufReader::new(file)));
+  match solution {
+    Ok(solution) => display_solution(&problem, &solution),
+    Err(e) => {
+      eprintln!("{}: {}", solution_path, e);
+      process::exit(1);
+    }
+  }
+  Ok(())
+}
+
+/// Checks a schedule found elsewhere, failing if it is infeasible
+fn verify_command(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let runways = args.count("--runways", 1)?;
+  let first_id = if args.flag("--one-based") { 1 } else { 0 };
+  let cross_separation = args.option("--cross-separation")?;
+  let file_path: String = args.positional("instance")?;
+  let schedule_path: String = args.positional("schedule")?;
+  args.finish()?;
+  let entries = read_schedule_from(&schedule_path, first_id).unwrap_or_else(|e| {
+    eprintln!("{}: {}", schedule_path, e);
+    process::exit(1);
+  });
+  // Without --runways, as many as the schedule uses
+  let runways = runways.unwrap_or_else(|| entries.iter().map(|e| e.runway + 1).max().unwrap_or(1));
+  let problem = load_problem(&file_path, common.mode).with_runways(runways);
+  let problem = with_cross_separation(problem, cross_separation.as_deref(), common.mode);
+  let verification = verify(&problem, &entries);
+  for violation in &verification.violations {
+    println!("Violation: {}", violation);
+  }
+  display_costs(&problem, &verification.solution);
+  println!("Feasible={}", verification.is_feasible());
+  if !verification.is_feasible() {
+    process::exit(1);
+  }
+  Ok(())
+}
+
+/// Writes a random instance, to stdout without an output file
+fn generate_command(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let defaults = GeneratorSettings::default();
+  let planes = args.value("--planes")?.unwrap_or(defaults.planes);
+  // Without --spread, targets are as dense as with the default settings
+  let target_spread = args.value("--spread")?.unwrap_or_else(|| {
+    let spread = planes.saturating_mul(defaults.target_spread as usize) / defaults.planes;
+    u32::try_from(spread).unwrap_or(u32::MAX)
+  });
+  let class_weights = match args.list::<f64>("--mix", 3)? {
+    Some(w) if w.iter().any(|&w| !(w.is_finite() && w >= 0.0)) || w.iter().all(|&w| w == 0.0) => {
+      return Err(UsageError(
+        "--mix needs finite non-negative weights, one of them positive".to_string(),
+      ));
+    }
+    Some(w) => [w[0], w[1], w[2]],
+    None => defaults.class_weights,
+  };
+  let separation = match args.list::<u32>("--separation", 9)? {
+    Some(s) => [[s[0], s[1], s[2]], [s[3], s[4], s[5]], [s[6], s[7], s[8]]],
+    None => defaults.separation,
+  };
+  let settings = GeneratorSettings {
+    planes,
+    target_spread,
+    before_target: args.range("--before")?.unwrap_or(defaults.before_target),
+    after_target: args.range("--after")?.unwrap_or(defaults.after_target),
+    lead_time: args.range("--lead")?.unwrap_or(defaults.lead_time),
+    penalty_before: args
+      .range("--penalty-before")?
+      .unwrap_or(defaults.penalty_before),
+    penalty_after: args
+      .range("--penalty-after")?
+      .unwrap_or(defaults.penalty_after),
+    class_weights,
+    separation,
+    freeze_time: args.value("--freeze")?.unwrap_or(defaults.freeze_time),
+  };
+  let output: Option<String> = if args.0.is_empty() {
+    None
+  } else {
+    Some(args.positional("output")?)
+  };
+  args.finish()?;
+
+  let mut rng = StdRng::seed_from_u64(resolve_seed(common.seed));
+  let data = generate(&settings, &mut rng).map_err(|e| UsageError(e.to_string()))?;
+  let written = match &output {
+    None => data.write(&mut io::stdout().lock()),
+    Some(output) => File::create(output).and_then(|file| {
+      let mut writer = io::BufWriter::new(file);
+      data.write(&mut writer)?;
+      writer.flush()
+    }),
+  };
+  if let Err(e) = written {
+    eprintln!("{}: {}", output.as_deref().unwrap_or("stdout"), e);
+    process::exit(1);
+  }
+  Ok(())
+}
+
+fn compare(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let configurations: Vec<Configuration> = args
+    .options("--config")?
+    .iter()
+    .map(|label| Configuration::parse(label))
+    .try_collect()?;
+  let max_time = args.value("--time")?.unwrap_or(0.0);
+  let runways = args.count("--runways", 1)?.unwrap_or(1);
+  let runs = args.count("--runs", 1)?.unwrap_or(1);
+  let workers = args
+    .count("--workers", 1)?
+    .unwrap_or_else(available_workers);
+  let folder_path: String = args.positional("folder")?;
+  args.finish()?;
+  if configurations.len() < 2 {
+    return Err(UsageError("at least two --config are needed".to_string()));
+  }
+  let problems = load_folder(&folder_path, common.mode, runways);
+  if problems.is_empty() {
+    return Err(UsageError(format!("no instances in {}", folder_path)));
+  }
+  let seed = resolve_seed(common.seed);
+  let k = configurations.len();
+
+  // A block is run `b % runs` of instance `b / runs`. Every configuration
+  // gets the same seed on a block, and without a time limit, the same seed
+  // always gives the same costs
+  let costs = run_parallel(problems.len() * runs * k, workers, |job| {
+    let (block, configuration) = (job / k, &configurations[job % k]);
+    let problem = &problems[block / runs].1;
+    let mut rng = StdRng::seed_from_u64(job_seed(seed, block as u64));
+    let (solution, _) = if max_time > 0.0 {
+      configuration.solve(problem, max_time, &mut rng)
+    } else {
+      configuration.run_bounded(problem, &mut rng)
+    };
+    problem.cost(&solution)
+  });
+  let blocks: Vec<Vec<f64>> = costs.chunks(k).map(<[f64]>::to_vec).collect();
+  let column = |c: usize| blocks.iter().map(|block| block[c]).collect::<Vec<_>>();
+
+  let friedman = friedman(&blocks);
+  println!("#\tMeanRank\tMeanCost\tConfiguration");
+  for (c, configuration) in configurations.iter().enumerate() {
+    println!(
+      "{}\t{:.3}\t{:.2}\t{}",
+      c + 1,
+      friedman.mean_ranks[c],
+      Summary::of(&column(c)).mean,
+      configuration.label
+    );
+  }
+  println!(
+    "Friedman\tChi2={:.3}\tDf={}\tP={:.4}\tBlocks={}",
+    friedman.statistic, friedman.df, friedman.p_value, friedman.blocks
+  );
+  let post_hoc = friedman_post_hoc(&friedman);
+  println!("Pair\tW+\tW-\tWilcoxonP\tPostHocP\tBetter");
+  let pairs = (0..k).flat_map(|a| (a + 1..k).map(move |b| (a, b)));
+  for (a, b) in pairs {
+    let wilcoxon = wilcoxon_signed_rank(&column(a), &column(b));
+    let marker = |p: f64| if p < SIGNIFICANCE { "*" } else { "" };
+    // Lower costs rank first, the better configuration has the lower rank
+    let better = if post_hoc[a][b] >= SIGNIFICANCE {
+      "-".to_string()
+    } else if friedman.mean_ranks[a] < friedman.mean_ranks[b] {
+      (a + 1).to_string()
+    } else {
+      (b + 1).to_string()
+    };
+    println!(
+      "{}-{}\t{}\t{}\t{:.4}{}\t{:.4}{}\t{}",
+      a + 1,
+      b + 1,
+      wilcoxon.w_plus,
+      wilcoxon.w_minus,
+      wilcoxon.p_value,
+      marker(wilcoxon.p_value),
+      post_hoc[a][b],
+      marker(post_hoc[a][b]),
+      better
+    );
+  }
+  Ok(())
+}
+
+fn tune(args: &mut Arguments, common: &Common) -> Result<(), UsageError> {
+  let budget = args
+    .value("--budget")?
+    .unwrap_or(RaceSettings::default().budget);
+  let output: Option<String> = args.option("--output")?;
+  let runways = args.count("--runways", 1)?.unwrap_or(1);
+  let workers = args
+    .count("--workers", 1)?
+    .unwrap_or_else(available_workers);
+  let parameters_path: String = args.positional("parameters")?;
+  let folder_path: String = args.positional("folder")?;
+  args.finish()?;
+  let space = match ParameterSpace::read_from(&parameters_path) {
+    Ok(space) => space,
+    Err(e) => {
+      eprintln!("{}: {}", parameters_path, e);
+      process::exit(1);
+    }
+  };
+  let problems = load_folder(&folder_path, common.mode, runways);
+  if problems.is_empty() {
+    return Err(UsageError(format!("no instances in {}", folder_path)));
+  }
+  let settings = RaceSettings {
+    budget,
+    ..RaceSettings::default()
+  };
+  // Every evaluation is a bounded run, like the irace command, so that the
+  // same seed always gives the same elites
+  let mut rng = StdRng::seed_from_u64(resolve_seed(common.seed));
+  let elites = iterated_race(
+    &space,
+    problems.len(),
+    settings,
+    workers,
+    &mut rng,
+    |values, instance, seed| {
+      let command_line = space.command_line(values);
+      let configuration = Configuration::parse(&command_line).unwrap_or_else(|e| {
+        eprintln!("{}: {}", parameters_path, e);
+        process::exit(1);
+      });
+      let problem = &problems[instance].1;
+      let (solution, _) = configuration.run_bounded(problem, &mut StdRng::seed_from_u64(seed));
+      problem.cost(&solution)
+    },
+  );
+  println!("#\tMeanRank\tMeanCost\tInstances\tConfiguration");
+  for (k, elite) in elites.iter().enumerate() {
+    println!(
+      "{}\t{:.3}\t{:.2}\t{}\t{}",
+      k + 1,
+      elite.mean_rank,
+      elite.mean_cost,
+      elite.experiments,
+      space.command_line(&elite.values)
+    );
+  }
+  let output = output.unwrap_or_else(|| {
+    let path = std::path::Path::new(&parameters_path).with_file_name("elites.txt");
+    path.to_string_lossy().to_string()
+  });
+  let written = File::create(&output).and_then(|file| {
+    let mut writer = io::BufWriter::new(file);
+    space.write_configurations(&elites, &mut writer)?;
+    writer.flush()
+  });
+  match written {
+    Ok(()) => eprintln!("Elites written to {}", output),
+    Err(e) => {
+      eprintln!("{}: {}", output, e);
+      process::exit(1);
+    }
+  }
+  Ok(())
+}
+
+/// A mistake on the command line, reported with the usage of the command
+#[derive(Debug)]
+struct UsageError(String);
+
+impl fmt::Display for UsageError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+
+/// Command line arguments left to parse. Options are taken by name first,
+/// then positional arguments in order
+struct Arguments(Vec<String>);
+
+impl Arguments {
+  /// Removes the flag `name`, returns whether it was there
+  fn flag(&mut self, name: &str) -> bool {
+    let len = self.0.len();
+    self.0.retain(|arg| arg != name);
+    self.0.len() != len
+  }
+
+  /// Removes `name` and the value following it
+  fn option(&mut self, name: &str) -> Result<Option<String>, UsageError> {
+    let Some(i) = self.0.iter().position(|arg| arg == name) else {
+      return Ok(None);
+    };
+    if i + 1 >= self.0.len() {
+      return Err(UsageError(format!("missing value for {}", name)));
+    }
+    self.0.remove(i);
+    Ok(Some(self.0.remove(i)))
+  }
+
+  /// Removes every `name` and the values following them
+  fn options(&mut self, name: &str) -> Result<Vec<String>, UsageError> {
+    let mut values = Vec::new();
+    while let Some(value) = self.option(name)? {
+      values.push(value);
+    }
+    Ok(values)
+  }
+
+  /// `option` parsed as a `T`
+  fn value<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, UsageError>
+  where
+    T::Err: fmt::Display,
+  {
+    self
+      .option(name)?
+      .map(|value| parse_argument(name, &value))
+      .transpose()
+  }
+
+  /// `value` of a number of things, failing below `minimum`
+  fn count(&mut self, name: &str, minimum: usize) -> Result<Option<usize>, UsageError> {
+    let value = self.value(name)?;
+    if let Some(value) = value {
+      at_least(name, value, minimum)?;
+    }
+    Ok(value)
+  }
+
+  /// `option` as `len` values separated by commas
+  fn list<T: FromStr>(&mut self, name: &str, len: usize) -> Result<Option<Vec<T>>, UsageError>
+  where
+    T::Err: fmt::Display,
+  {
+    let Some(value) = self.option(name)? else {
+      return Ok(None);
+    };
+    let values: Vec<T> = value
+      .split(',')
+      .map(|value| parse_argument(name, value.trim()))
+      .try_collect()?;
+    if values.len() != len {
+      return Err(UsageError(format!(
+        "expected {} values separated by commas for {}",
+        len, name
+      )));
+    }
+    Ok(Some(values))
+  }
+
+  /// `list` of a minimum and a maximum
+  fn range<T: FromStr + PartialOrd + Copy>(
+    &mut self,
+    name: &str,
+  ) -> Result<Option<RangeInclusive<T>>, UsageError>
+  where
+    T::Err: fmt::Display,
+  {
+    match self.list(name, 2)? {
+      Some(values) if values[0] > values[1] => Err(UsageError(format!(
+        "the minimum of {} is above its maximum",
+        name
+      ))),
+      values => Ok(values.map(|values| values[0]..=values[1])),
+    }
+  }
+
+  /// Removes the next positional argument, once the options are taken
+  fn positional<T: FromStr>(&mut self, name: &str) -> Result<T, UsageError>
+  where
+    T::Err: fmt::Display,
+  {
+    match self.0.first() {
+      None => Err(UsageError(format!("missing <{}>", name))),
+      Some(arg) if arg.starts_with("--") => Err(UsageError(format!("unknown option {}", arg))),
+      Some(_) => {
+        let value = self.0.remove(0);
+        parse_argument(name, &value)
+      }
+    }
+  }
+
+  /// Removes every positional argument left, once the options are taken
+  fn positionals<T: FromStr>(&mut self, name: &str) -> Result<Vec<T>, UsageError>
+  where
+    T::Err: fmt::Display,
+  {
+    let mut values = Vec::new();
+    while !self.0.is_empty() {
+      values.push(self.positional(name)?);
+    }
+    Ok(values)
+  }
+
+  /// Fails if any argument is left
+  fn finish(&self) -> Result<(), UsageError> {
+    match self.0.first() {
+      None => Ok(()),
+      Some(arg) if arg.starts_with("--") => Err(UsageError(format!("unknown option {}", arg))),
+      Some(arg) => Err(UsageError(format!("unexpected argument {:?}", arg))),
+    }
+  }
+}
+
+/// Fails if `value`, given for `name`, is below `minimum`
+fn at_least(name: &str, value: usize, minimum: usize) -> Result<(), UsageError> {
+  if value < minimum {
+    return Err(UsageError(format!("{} must be at least {}", name, minimum)));
+  }
+  Ok(())
+}
+
+/// Fails unless `value`, given for `name`, is finite and positive
+fn positive(name: &str, value: f64) -> Result<(), UsageError> {
+  if !(value.is_finite() && value > 0.0) {
+    return Err(UsageError(format!("{} must be finite and positive", name)));
+  }
+  Ok(())
+}
+
+fn parse_argument<T: FromStr>(name: &str, value: &str) -> Result<T, UsageError>
+where
+  T::Err: fmt::Display,
+{
+  value
+    .parse()
+    .map_err(|e| UsageError(format!("invalid value {:?} for {}: {}", value, name, e)))
+}
+
+struct Command {
+  name: &'static str,
+  /// Positional arguments
+  arguments: &'static str,
+  about: &'static str,
+  options: &'static [&'static str],
+  run: fn(&mut Arguments, &Common) -> Result<(), UsageError>,
+}
+
+impl Command {
+  fn help(&self) -> String {
+    let mut help = format!(
+      "{}\n\nUsage: {} {} [options] {}\n\nOptions:\n",
+      self.about, PROGRAM, self.name, self.arguments
+    );
+    for options in self.options {
+      help.push_str(options);
+    }
+    help + COMMON_OPTIONS
+  }
+}
+
+const PROGRAM: &str = "airplane-landing-scheduler";
+
+const COMMON_OPTIONS: &str = concat!(
+  "  --seed <seed>           Seed of the random number generator [default: random]\n",
+  "                          Time limited runs restart as often as time allows, so that\n",
+  "                          they only repeat with --bounded, or --time 0 in compare\n",
+  "  --strict                Reject malformed separation times instead of reading them as 0\n",
+  "                          and target times outside of landing windows\n",
+  "  -h, --help              Print help\n",
+);
+
+const RUNWAYS_OPTION: &str = "  --runways <n>           Number of runways [default: 1]\n";
+
+const CROSS_SEPARATION_OPTION: &str = concat!(
+  "  --cross-separation <file>\n",
+  "                          Separation times between planes on different runways, in rows\n",
+  "                          laid out as the instance ones [default: none, runways are\n",
+  "                          independent]\n",
+);
+
+const WORKERS_OPTION: &str =
+  "  --workers <n>           Threads running jobs [default: available parallelism]\n";
+
+const SOLVER_OPTIONS: &str = concat!(
+  "  --algorithm <name>      sa, tabu, ga, vns, ils or hc [default: sa]\n",
+  "  --sa-max-k <k>          SA iterations per temperature, per plane [default: 3.8272]\n",
+  "  --alpha <alpha>         SA cooling factor [default: 0.9809]\n",
+  "  --initial-temp <temp>   SA initial temperature [default: 10599.1518]\n",
+  "  --tenure <n>            Tabu tenure [default: 20]\n",
+  "  --candidates <n>        Tabu moves sampled per iteration [default: 100]\n",
+  "  --population <n>        GA population [default: 50]\n",
+  "  --ils-gas <n>           ILS perturbations without improvement [default: 100]\n",
+  "  --climb-gas <n>         Hill climbing moves without improvement [default: planes]\n",
+);
+
+const START_OPTIONS: &str = concat!(
+  "  --construct <name>      Start from the fcfs, edf or lci heuristic\n",
+  "  --grasp <iterations>    Start from the best of GRASP constructions\n",
+  "  --rcl <alpha>           GRASP restricted candidate list alpha [default: 0.3]\n",
+);
+
+const COMMANDS: [Command; 12] = [
+  Command {
+    name: "solve",
+    arguments: "[<instance>...]",
+    about: "Solve instances, rerunning the algorithm until the time limit",
+    options: &[
+      "  --config-file <path>    TOML or JSON run configuration, overridden by the options\n",
+      "  --time <seconds>        Time limit of every instance [default: 1]\n",
+      "  --bounded               Run the algorithm once, bounded, instead\n",
+      "  --format <format>       table, summary, json or csv [default: table]\n",
+      RUNWAYS_OPTION,
+      CROSS_SEPARATION_OPTION,
+      SOLVER_OPTIONS,
+      START_OPTIONS,
+    ],
+    run: solve,
+  },
+  Command {
+    name: "eval-all",
+    arguments: "<folder>",
+    about: "Solve every instance of a folder and compare with the best known costs",
+    options: &[
+      "  --time <seconds>        Time limit of every run [default: 1]\n",
+      "  --bounded               Run the algorithm once per run, bounded, instead\n",
+      "  --runs <n>              Runs per instance [default: 1]\n",
+      "  --best-known <csv>      Best known costs overriding the built-in ones, in rows\n",
+      "                          instance,runways,cost[,optimal]\n",
+      RUNWAYS_OPTION,
+      WORKERS_OPTION,
+      SOLVER_OPTIONS,
+      START_OPTIONS,
+    ],
+    run: eval_all,
+  },
+  Command {
+    name: "irace",
+    arguments: "<sa_max_k> <alpha> <initial_temp> <instance>",
+    about: "Run once, bounded, and print the cost and the time, for tuning/target-runner",
+    options: &[SOLVER_OPTIONS, START_OPTIONS],
+    run: irace,
+  },
+  Command {
+    name: "simulate",
+    arguments: "<instance>",
+    about: "Replay the appearances of the planes, rescheduling at every event",
+    options: &[RUNWAYS_OPTION, CROSS_SEPARATION_OPTION, SOLVER_OPTIONS],
+    run: simulate_command,
+  },
+  Command {
+    name: "baselines",
+    arguments: "<instance>",
+    about: "Compare the constructive heuristics",
+    options: &[RUNWAYS_OPTION, CROSS_SEPARATION_OPTION],
+    run: baselines,
+  },
+  Command {
+    name: "exact",
+    arguments: "<instance>",
+    about: "Solve an instance by branch and bound",
+    options: &[
+      "  --time <seconds>        Time limit [default: 60]\n",
+      "  --nodes <n>             Node limit [default: none]\n",
+      RUNWAYS_OPTION,
+      CROSS_SEPARATION_OPTION,
+      START_OPTIONS,
+    ],
+    run: exact,
+  },
+  Command {
+    name: "mip",
+    arguments: "<instance> <model.lp|model.mps>",
+    about: "Write the MIP model of an instance",
+    options: &[RUNWAYS_OPTION, CROSS_SEPARATION_OPTION],
+    run: mip,
+  },
+  Command {
+    name: "mip-solution",
+    arguments: "<instance> <solution>",
+    about: "Read back the solution of a MIP solver",
+    options: &[RUNWAYS_OPTION, CROSS_SEPARATION_OPTION],
+    run: mip_solution,
+  },
+  Command {
+    name: "verify",
+    arguments: "<instance> <schedule>",
+    about: "Check a schedule of plane, landing time and runway rows, failing if infeasible",
+    options: &[
+      "  --runways <n>           Number of runways [default: as many as the schedule uses]\n",
+      CROSS_SEPARATION_OPTION,
+      "  --one-based             Plane ids of the schedule start at 1\n",
+    ],
+    run: verify_command,
+  },
+  Command {
+    name: "generate",
+    arguments: "[<output>]",
+    about: "Write a random instance in the OR-Library format, to stdout without <output>",
+    options: &[
+      "  --planes <n>            Number of planes [default: 100]\n",
+      "  --spread <time>         Span of the target times [default: 8 per plane]\n",
+      "  --before <min,max>      Earliest landing time before the target [default: 10,100]\n",
+      "  --after <min,max>       Latest landing time after the target [default: 200,500]\n",
+      "  --lead <min,max>        Appearance time before the earliest landing [default: 60,90]\n",
+      "  --penalty-before <min,max>\n",
+      "                          Cost per time unit before the target [default: 10,30]\n",
+      "  --penalty-after <min,max>\n",
+      "                          Cost per time unit after the target [default: 10,30]\n",
+      "  --mix <h,m,l>           Weights of the heavy, medium and light wake classes\n",
+      "                          [default: 0.2,0.5,0.3]\n",
+      "  --separation <hh,hm,...,ll>\n",
+      "                          Separation times of a follower class after a leader class,\n",
+      "                          by leader [default: 8,10,12,6,6,10,6,6,6]\n",
+      "  --freeze <time>         Freeze time [default: 30]\n",
+    ],
+    run: generate_command,
+  },
+  Command {
+    name: "compare",
+    arguments: "<folder>",
+    about: "Compare configurations on the instances of a folder with paired tests",
+    options: &[
+      "  --config <options>      Solver options of a configuration, repeated for each\n",
+      "  --time <seconds>        Time limit of every run, 0 for one bounded run [default: 0]\n",
+      "  --runs <n>              Seeds per instance [default: 1]\n",
+      RUNWAYS_OPTION,
+      WORKERS_OPTION,
+    ],
+    run: compare,
+  },
+  Command {
+    name: "tune",
+    arguments: "<parameters.txt> <folder>",
+    about: "Tune solver options by iterated racing over the instances of a folder",
+    options: &[
+      "  --budget <runs>         Runs of the solver in total [default: 1000]\n",
+      "  --output <file>         Elite configurations [default: elites.txt beside parameters]\n",
+      RUNWAYS_OPTION,
+      WORKERS_OPTION,
+    ],
+    run: tune,
+  },
+];
+
+fn usage() -> String {
+  let mut usage = format!(
+    "Usage: {} <command> [options] <arguments>\n\nCommands:\n",
+    PROGRAM
+  );
+  for command in &COMMANDS {
+    usage.push_str(&format!("  {:<14}{}\n", command.name, command.about));
+  }
+  usage + "\nSee the options of a command with --help\n"
+}
+
+fn main() {
+  let mut args = Arguments(args().skip(1).collect());
+  let name = match args.0.first().map(String::as_str) {
+    None => {
+      eprint!("{}", usage());
+      process::exit(2);
+    }
+    Some("-h" | "--help" | "help") => {
+      print!("{}", usage());
+      return;
+    }
+    Some(_) => args.0.remove(0),
+  };
+  let Some(command) = COMMANDS.iter().find(|command| command.name == name) else {
+    eprint!("error: unknown command {:?}\n\n{}", name, usage());
+    process::exit(2);
+  };
+  if args.flag("--help") || args.flag("-h") {
+    print!("{}", command.help());
+    return;
+  }
+  let result = Common::take(&mut args).and_then(|common| (command.run)(&mut args, &common));
+  if let Err(e) = result {
+    eprintln!(
+      "error: {}\n\nUsage: {} {} [options] {}\nSee the options with --help",
+      e, PROGRAM, command.name, command.arguments
+    );
+    process::exit(2);
+  }
 }
 
 // fn main() {
@@ -244,3 +1841,89 @@ fn main() {
 //     problem.cost(&solution)
 //   );
 // }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn arguments(args: &[&str]) -> Arguments {
+    Arguments(args.iter().map(|arg| arg.to_string()).collect())
+  }
+
+  /// The configuration of `text` as `solve` would run it, without options
+  fn solve_configuration(text: &str, json: bool) -> Result<RunConfig, UsageError> {
+    let mut run = RunConfig::parse(text, json).unwrap();
+    run.algorithm = run.algorithm.take(&mut arguments(&[]))?;
+    run.start = run.start.take(&mut arguments(&[]))?;
+    Ok(run)
+  }
+
+  #[test]
+  fn loads_run_configurations() {
+    let text = concat!(
+      "instances = [\"data/airland1.txt\"]\n",
+      "runways = 2\n",
+      "cross_separation = \"cross.txt\"\n",
+      "[algorithm]\n",
+      "name = \"sa\"\n",
+      "alpha = 0.9\n",
+      "[start]\n",
+      "name = \"grasp\"\n",
+      "iterations = 5\n",
+    );
+    let run = solve_configuration(text, false).unwrap();
+    assert_eq!(run.runways, 2);
+    assert_eq!(run.cross_separation.as_deref(), Some("cross.txt"));
+    assert_eq!(
+      run.algorithm,
+      Algorithm::SimulatedAnnealing {
+        sa_max_k: default_sa_max_k(),
+        alpha: 0.9,
+        initial_temp: default_initial_temp(),
+      }
+    );
+    assert_eq!(
+      run.start,
+      Start::Grasp {
+        iterations: 5,
+        alpha: default_rcl()
+      }
+    );
+    let json = r#"{"algorithm": {"name": "tabu", "candidates": 10}}"#;
+    let run = solve_configuration(json, true).unwrap();
+    assert_eq!(
+      run.algorithm,
+      Algorithm::Tabu {
+        tenure: default_tenure(),
+        candidates: 10
+      }
+    );
+    assert!(RunConfig::parse("[algorithm]\nname = \"sa\"\nbeta = 1\n", false).is_err());
+  }
+
+  #[test]
+  fn rejects_sa_parameters_it_cannot_run_with() {
+    for (field, value) in [
+      ("initial_temp", "nan"),
+      ("initial_temp", "inf"),
+      ("initial_temp", "0.0"),
+      ("sa_max_k", "-1.0"),
+      ("sa_max_k", "0.0"),
+      ("alpha", "1.0"),
+    ] {
+      let text = format!("[algorithm]\nname = \"sa\"\n{} = {}\n", field, value);
+      assert!(solve_configuration(&text, false).is_err(), "{}", text);
+    }
+    for (option, value) in [
+      ("--initial-temp", "inf"),
+      ("--initial-temp", "NaN"),
+      ("--sa-max-k", "0"),
+      ("--alpha", "0"),
+    ] {
+      let result = Algorithm::default().take(&mut arguments(&[option, value]));
+      assert!(result.is_err(), "{} {}", option, value);
+    }
+    let result = Algorithm::default().take(&mut arguments(&["--initial-temp", "5e3"]));
+    assert!(result.is_ok());
+  }
+}
@@ -1,11 +1,24 @@
+use rand::seq::SliceRandom;
 use rand::Rng;
+use std::collections::HashMap;
+use std::hash::Hash;
 
 pub trait Problem<S> {
+  /// Incremental evaluation of the problem, usually `Self`, or
+  /// `NotIncremental` if it has none
+  type Incremental: IncrementalProblem<S>;
+
   fn initial_solution(&self) -> S;
-  fn random_neighbor(&self, solution: &S) -> S;
-  fn first_improvement_neighbor(&self, solution: &S) -> S;
+  fn random_neighbor<R: Rng + ?Sized>(&self, solution: &S, rng: &mut R) -> S;
+  fn first_improvement_neighbor<R: Rng + ?Sized>(&self, solution: &S, rng: &mut R) -> S;
   fn cost(&self, solution: &S) -> f64;
 
+  /// The metaheuristics evaluate moves through this instead of building and
+  /// evaluating neighbors, when it is not `None`
+  fn incremental(&self) -> Option<&Self::Incremental> {
+    None
+  }
+
   fn best_solution(&self, solutions: Vec<S>) -> S {
     solutions
       .into_iter()
@@ -13,20 +26,149 @@ pub trait Problem<S> {
       .unwrap()
   }
 
-  fn shake(&self, mut solution: S, intensity: i32) -> S {
+  fn shake<R: Rng + ?Sized>(&self, mut solution: S, intensity: i32, rng: &mut R) -> S {
     for _ in 0..intensity {
-      solution = self.random_neighbor(&solution);
+      solution = self.random_neighbor(&solution, rng);
     }
     solution
   }
 }
 
+/// Problem whose moves have a cost delta that can be computed without
+/// building the neighbor they lead to
+pub trait IncrementalProblem<S>: Problem<S> {
+  type Move;
+
+  /// Random move, as done by `random_neighbor`, with the cost change it makes.
+  /// `None` if the solution has nothing to move
+  fn random_move<R: Rng + ?Sized>(&self, solution: &S, rng: &mut R) -> Option<(Self::Move, f64)>;
+
+  /// Move lowering the cost, as found by `first_improvement_neighbor`, with
+  /// the cost change it makes. `None` if there is no such move
+  fn first_improvement_move<R: Rng + ?Sized>(
+    &self,
+    solution: &S,
+    rng: &mut R,
+  ) -> Option<(Self::Move, f64)>;
+
+  fn apply_move(&self, solution: &mut S, m: Self::Move);
+}
+
+/// `Problem::Incremental` of the problems without incremental evaluation,
+/// which has no values
+#[derive(Debug, Clone, Copy)]
+pub enum NotIncremental {}
+
+impl<S> Problem<S> for NotIncremental {
+  type Incremental = Self;
+
+  fn initial_solution(&self) -> S {
+    match *self {}
+  }
+
+  fn random_neighbor<R: Rng + ?Sized>(&self, _: &S, _: &mut R) -> S {
+    match *self {}
+  }
+
+  fn first_improvement_neighbor<R: Rng + ?Sized>(&self, _: &S, _: &mut R) -> S {
+    match *self {}
+  }
+
+  fn cost(&self, _: &S) -> f64 {
+    match *self {}
+  }
+}
+
+impl<S> IncrementalProblem<S> for NotIncremental {
+  type Move = NotIncremental;
+
+  fn random_move<R: Rng + ?Sized>(&self, _: &S, _: &mut R) -> Option<(Self::Move, f64)> {
+    match *self {}
+  }
+
+  fn first_improvement_move<R: Rng + ?Sized>(&self, _: &S, _: &mut R) -> Option<(Self::Move, f64)> {
+    match *self {}
+  }
+
+  fn apply_move(&self, _: &mut S, m: Self::Move) {
+    match m {}
+  }
+}
+
+/// Problem whose moves can be made tabu, through the attributes they add to
+/// and drop from a solution
+pub trait TabuProblem<S>: IncrementalProblem<S> {
+  /// Feature of a solution, for example a plane landing in some time range
+  type Attribute: Eq + Hash;
+
+  /// Attribute `m` drops from `solution`, which becomes tabu once `m` is made
+  fn dropped_attribute(&self, solution: &S, m: &Self::Move) -> Self::Attribute;
+  /// Attribute `m` adds to `solution`, `m` is tabu if that attribute is
+  fn added_attribute(&self, solution: &S, m: &Self::Move) -> Self::Attribute;
+}
+
+/// Problem whose solutions can be recombined by a genetic algorithm
+pub trait Recombinable<S>: Problem<S> {
+  /// Recombination operator
+  type Crossover: Copy;
+
+  /// Child of `a` and `b`
+  fn crossover<R: Rng + ?Sized>(&self, crossover: Self::Crossover, a: &S, b: &S, rng: &mut R) -> S;
+}
+
+/// Problem with several neighborhood structures, for variable neighborhood
+/// search
+pub trait Neighborhoods<S>: Problem<S> {
+  type Neighborhood: Copy;
+
+  /// Neighborhood structures, in the order they are explored
+  fn neighborhoods(&self) -> Vec<Self::Neighborhood>;
+  fn random_neighbor_in<R: Rng + ?Sized>(
+    &self,
+    neighborhood: Self::Neighborhood,
+    solution: &S,
+    rng: &mut R,
+  ) -> S;
+  /// Neighbor in `neighborhood` with a lower cost, `None` if there is none
+  fn first_improvement_in<R: Rng + ?Sized>(
+    &self,
+    neighborhood: Self::Neighborhood,
+    solution: &S,
+    rng: &mut R,
+  ) -> Option<S>;
+}
+
+/// Problem with a randomized greedy construction, for GRASP
+pub trait RandomizedConstruction<S>: Problem<S> {
+  /// Solution built step by step, every step picking at random one of the
+  /// candidates whose greedy value is within `alpha` of the best one, as a
+  /// fraction of the range of values. 0 is purely greedy, 1 purely random
+  fn greedy_randomized<R: Rng + ?Sized>(&self, alpha: f64, rng: &mut R) -> S;
+}
+
 const NEIGHBORS: usize = 5;
 
-pub fn hill_climb<S, P: Problem<S>>(problem: &P, mut s: S, gas: usize) -> S {
+pub fn hill_climb<S, P: Problem<S>, R: Rng + ?Sized>(
+  problem: &P,
+  mut s: S,
+  gas: usize,
+  rng: &mut R,
+) -> S {
+  if let Some(incremental) = problem.incremental() {
+    for _ in 0..gas {
+      let best_move = (0..NEIGHBORS)
+        .filter_map(|_| incremental.first_improvement_move(&s, rng))
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+      match best_move {
+        Some((m, _)) => incremental.apply_move(&mut s, m),
+        None => break,
+      }
+    }
+    return s;
+  }
   for _ in 0..gas {
     let mut neighbors: Vec<_> = (0..NEIGHBORS)
-      .map(|_| problem.first_improvement_neighbor(&s))
+      .map(|_| problem.first_improvement_neighbor(&s, rng))
       .collect();
     neighbors.push(s);
     s = problem.best_solution(neighbors);
@@ -34,20 +176,38 @@ pub fn hill_climb<S, P: Problem<S>>(problem: &P, mut s: S, gas: usize) -> S {
   s
 }
 
-pub fn ils<S: Clone, P: Problem<S>>(problem: &P, ils_gas: usize, climb_gas: usize) -> S {
-  let mut s = problem.initial_solution();
-  s = hill_climb(problem, s, climb_gas);
+pub fn ils<S: Clone, P: Problem<S>, R: Rng + ?Sized>(
+  problem: &P,
+  ils_gas: usize,
+  climb_gas: usize,
+  rng: &mut R,
+) -> S {
+  ils_from(problem, problem.initial_solution(), ils_gas, climb_gas, rng)
+}
+
+/// `ils` starting from `s` instead of the initial solution
+pub fn ils_from<S: Clone, P: Problem<S>, R: Rng + ?Sized>(
+  problem: &P,
+  mut s: S,
+  ils_gas: usize,
+  climb_gas: usize,
+  rng: &mut R,
+) -> S {
+  s = hill_climb(problem, s, climb_gas, rng);
+  let mut s_cost = problem.cost(&s);
   let mut i = 0;
   let mut best_i = 0;
   let mut intensity = 1;
   while i - best_i < ils_gas {
     i += 1;
-    let s_shake = problem.shake(s.clone(), intensity);
-    let s_shake = hill_climb(problem, s_shake, climb_gas);
-    if problem.cost(&s_shake) < problem.cost(&s) {
+    let s_shake = problem.shake(s.clone(), intensity, rng);
+    let s_shake = hill_climb(problem, s_shake, climb_gas, rng);
+    let shake_cost = problem.cost(&s_shake);
+    if shake_cost < s_cost {
       best_i = i;
       intensity = 1;
       s = s_shake;
+      s_cost = shake_cost;
     } else {
       intensity += 1;
     }
@@ -55,27 +215,101 @@ pub fn ils<S: Clone, P: Problem<S>>(problem: &P, ils_gas: usize, climb_gas: usiz
   s
 }
 
+/// Greedy randomized adaptive search procedure: the best of `iterations`
+/// randomized greedy constructions, each improved by `local_search`, for
+/// example `hill_climb` or `vnd`
+pub fn grasp<S, P: RandomizedConstruction<S>, R: Rng + ?Sized>(
+  problem: &P,
+  iterations: usize,
+  alpha: f64,
+  mut local_search: impl FnMut(S, &mut R) -> S,
+  rng: &mut R,
+) -> S {
+  let solutions = (0..iterations)
+    .map(|_| {
+      let s = problem.greedy_randomized(alpha, rng);
+      local_search(s, rng)
+    })
+    .collect();
+  problem.best_solution(solutions)
+}
+
+/// Variable neighborhood descent: improves `s` in the first neighborhood,
+/// moving on to the next one when it cannot and back to the first one once
+/// it does. Returns a local optimum of every neighborhood
+pub fn vnd<S, P: Neighborhoods<S>, R: Rng + ?Sized>(problem: &P, mut s: S, rng: &mut R) -> S {
+  let neighborhoods = problem.neighborhoods();
+  let mut k = 0;
+  while k < neighborhoods.len() {
+    match problem.first_improvement_in(neighborhoods[k], &s, rng) {
+      Some(better) => {
+        s = better;
+        k = 0;
+      }
+      None => k += 1,
+    }
+  }
+  s
+}
+
+/// Variable neighborhood search: shakes the solution in the `k`-th
+/// neighborhood and descends with `vnd`, going back to the first neighborhood
+/// on improvement and on to the next one otherwise, `max_iterations` times
+pub fn vns<S: Clone, P: Neighborhoods<S>, R: Rng + ?Sized>(
+  problem: &P,
+  s: &S,
+  max_iterations: usize,
+  rng: &mut R,
+) -> S {
+  let neighborhoods = problem.neighborhoods();
+  let mut s = vnd(problem, s.clone(), rng);
+  let mut s_cost = problem.cost(&s);
+  let mut k = 0;
+  for _ in 0..max_iterations {
+    let shaken = problem.random_neighbor_in(neighborhoods[k], &s, rng);
+    let candidate = vnd(problem, shaken, rng);
+    let cost = problem.cost(&candidate);
+    if cost < s_cost {
+      s = candidate;
+      s_cost = cost;
+      k = 0;
+    } else {
+      k = (k + 1) % neighborhoods.len();
+    }
+  }
+  s
+}
+
 type Temperature = f64;
-pub fn initial_temperature<S: Clone, P: Problem<S>>(
+
+/// Metropolis criterion
+fn accept<R: Rng + ?Sized>(delta: f64, temp: Temperature, rng: &mut R) -> bool {
+  delta < 0.0 || rng.gen_range(0.0..=1.0) < (-delta / temp).exp()
+}
+
+pub fn initial_temperature<S: Clone, P: Problem<S>, R: Rng + ?Sized>(
   problem: &P,
   s: &S,
   beta: f64,
   gamma: f64,
   sa_gas: usize,
   mut temp: Temperature,
+  rng: &mut R,
 ) -> Temperature {
   loop {
     let mut accepted = 0;
+    let s_cost = problem.cost(s);
     for _ in 0..sa_gas {
-      let neighbor = problem.random_neighbor(s);
-      let delta = problem.cost(&neighbor) - problem.cost(s);
-      if delta < 0.0 {
-        accepted += 1;
+      let delta = if let Some(incremental) = problem.incremental() {
+        // Nothing to move is a move that changes nothing
+        incremental
+          .random_move(s, rng)
+          .map_or(0.0, |(_, delta)| delta)
       } else {
-        let x = rand::thread_rng().gen_range(0.0..=1.0);
-        if x < (-delta / temp).exp() {
-          accepted += 1;
-        }
+        problem.cost(&problem.random_neighbor(s, rng)) - s_cost
+      };
+      if accept(delta, temp, rng) {
+        accepted += 1;
       }
     }
     if (accepted as f64) > (gamma * sa_gas as f64) {
@@ -87,18 +321,21 @@ pub fn initial_temperature<S: Clone, P: Problem<S>>(
   temp
 }
 
-pub fn simulated_annealing<S: Clone, P: Problem<S>>(
+pub fn simulated_annealing<S: Clone, P: Problem<S>, R: Rng + ?Sized>(
   problem: &P,
   s: &S,
   max_iterations: usize,
   alpha: f64,
   sa_max: usize,
   mut temp: Temperature,
+  rng: &mut R,
 ) -> S {
   let mut s = s.clone();
+  let mut s_cost = problem.cost(&s);
   let mut best_s = s.clone();
-  let mut best_cost = problem.cost(&best_s);
+  let mut best_cost = s_cost;
   let mut global_iter = 0;
+  let incremental = problem.incremental();
 
   while temp > 0.1 {
     for _ in 0..sa_max {
@@ -106,24 +343,140 @@ pub fn simulated_annealing<S: Clone, P: Problem<S>>(
       if global_iter > max_iterations {
         return best_s;
       }
-      let neighbor = problem.random_neighbor(&s);
-      let s_cost = problem.cost(&s);
-      let delta = problem.cost(&neighbor) - s_cost;
-      if delta < 0.0 {
-        s = neighbor;
-        if s_cost < best_cost {
-          best_s = s.clone();
-          best_cost = s_cost;
+      if let Some(incremental) = incremental {
+        if let Some((m, delta)) = incremental.random_move(&s, rng) {
+          if accept(delta, temp, rng) {
+            incremental.apply_move(&mut s, m);
+            s_cost += delta;
+          }
         }
       } else {
-        let x = rand::thread_rng().gen_range(0.0..=1.0);
-        if x < (-delta / temp).exp() {
+        let neighbor = problem.random_neighbor(&s, rng);
+        let delta = problem.cost(&neighbor) - s_cost;
+        if accept(delta, temp, rng) {
           s = neighbor;
+          s_cost += delta;
         }
       }
+      if s_cost < best_cost {
+        best_s = s.clone();
+        best_cost = s_cost;
+      }
     }
     temp *= alpha;
   }
   s = best_s;
   s
 }
+
+/// Makes the best of `candidates` random moves at every iteration, even when
+/// it raises the cost, except the moves bringing back an attribute dropped in
+/// the last `tenure` iterations. Those are still made when they lead to a new
+/// best solution (aspiration)
+pub fn tabu_search<S: Clone, P: TabuProblem<S>, R: Rng + ?Sized>(
+  problem: &P,
+  s: &S,
+  max_iterations: usize,
+  tenure: usize,
+  candidates: usize,
+  rng: &mut R,
+) -> S {
+  let mut s = s.clone();
+  let mut s_cost = problem.cost(&s);
+  let mut best_s = s.clone();
+  let mut best_cost = s_cost;
+  // Iteration until which each attribute is tabu
+  let mut tabu_until: HashMap<P::Attribute, usize> = HashMap::new();
+
+  for i in 0..max_iterations {
+    let best_move = (0..candidates)
+      .filter_map(|_| problem.random_move(&s, rng))
+      .filter(|(m, delta)| {
+        let tabu = tabu_until
+          .get(&problem.added_attribute(&s, m))
+          .is_some_and(|&until| until > i);
+        !tabu || s_cost + delta < best_cost
+      })
+      .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+    let Some((m, delta)) = best_move else {
+      continue;
+    };
+    tabu_until.insert(problem.dropped_attribute(&s, &m), i + tenure);
+    problem.apply_move(&mut s, m);
+    s_cost += delta;
+    if s_cost < best_cost {
+      best_s = s.clone();
+      best_cost = s_cost;
+    }
+  }
+  best_s
+}
+
+/// Parameters of `genetic_algorithm`
+#[derive(Debug, Clone, Copy)]
+pub struct GeneticParameters {
+  pub population: usize,
+  pub generations: usize,
+  /// Number of solutions competing to be a parent
+  pub tournament: usize,
+  /// Best solutions kept as they are in the next generation
+  pub elites: usize,
+  /// Probability of a child being replaced by a random neighbor
+  pub mutation_rate: f64,
+}
+
+/// Generational genetic algorithm: every generation keeps its elites and is
+/// filled with children of parents chosen by tournament, recombined with a
+/// random operator of `crossovers` and mutated with `random_neighbor`. The
+/// first population is made of increasingly shaken copies of `s`
+pub fn genetic_algorithm<S: Clone, P: Recombinable<S>, R: Rng + ?Sized>(
+  problem: &P,
+  s: &S,
+  crossovers: &[P::Crossover],
+  parameters: GeneticParameters,
+  rng: &mut R,
+) -> S {
+  assert!(parameters.elites < parameters.population);
+  let mut population: Vec<(S, f64)> = (0..parameters.population)
+    .map(|i| {
+      let s = problem.shake(s.clone(), i as i32, rng);
+      let cost = problem.cost(&s);
+      (s, cost)
+    })
+    .collect();
+
+  for _ in 0..parameters.generations {
+    population.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+    let mut next: Vec<(S, f64)> = population[..parameters.elites].to_vec();
+    while next.len() < parameters.population {
+      let a = tournament(&population, parameters.tournament, rng);
+      let b = tournament(&population, parameters.tournament, rng);
+      let crossover = *crossovers.choose(rng).unwrap();
+      let mut child = problem.crossover(crossover, a, b, rng);
+      if rng.gen_bool(parameters.mutation_rate) {
+        child = problem.random_neighbor(&child, rng);
+      }
+      let cost = problem.cost(&child);
+      next.push((child, cost));
+    }
+    population = next;
+  }
+  population
+    .into_iter()
+    .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+    .unwrap()
+    .0
+}
+
+/// Best of `size` solutions drawn at random from `population`
+fn tournament<'a, S, R: Rng + ?Sized>(
+  population: &'a [(S, f64)],
+  size: usize,
+  rng: &mut R,
+) -> &'a S {
+  &(0..size)
+    .map(|_| &population[rng.gen_range(0..population.len())])
+    .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+    .unwrap()
+    .0
+}
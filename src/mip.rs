@@ -0,0 +1,429 @@
+use crate::problem::{Arrival, LandingProblem, Solution, ID};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::{error, fmt};
+
+/// Terms written per line, LP readers limit the length of lines
+const TERMS_PER_LINE: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sense {
+  Equal,
+  GreaterOrEqual,
+  LessOrEqual,
+}
+
+#[derive(Debug, Clone)]
+pub struct Variable {
+  pub name: String,
+  pub lower: f64,
+  pub upper: f64,
+  pub binary: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Constraint {
+  pub name: String,
+  /// Coefficients by variable index
+  pub terms: Vec<(usize, f64)>,
+  pub sense: Sense,
+  pub rhs: f64,
+}
+
+/// Mixed integer linear program, minimising `objective`
+#[derive(Debug, Clone)]
+pub struct Model {
+  pub name: String,
+  pub variables: Vec<Variable>,
+  pub objective: Vec<(usize, f64)>,
+  pub constraints: Vec<Constraint>,
+}
+
+impl Model {
+  fn variable(&mut self, name: String, lower: f64, upper: f64, binary: bool) -> usize {
+    self.variables.push(Variable {
+      name,
+      lower,
+      upper,
+      binary,
+    });
+    self.variables.len() - 1
+  }
+
+  fn constrain(&mut self, name: String, terms: Vec<(usize, f64)>, sense: Sense, rhs: f64) {
+    let terms = terms.into_iter().filter(|&(_, c)| c != 0.0).collect();
+    self.constraints.push(Constraint {
+      name,
+      terms,
+      sense,
+      rhs,
+    });
+  }
+
+  fn write_terms(&self, w: &mut impl Write, terms: &[(usize, f64)]) -> io::Result<()> {
+    if terms.is_empty() {
+      return write!(w, " 0 {}", self.variables[0].name);
+    }
+    for (k, &(variable, coefficient)) in terms.iter().enumerate() {
+      if k > 0 && k % TERMS_PER_LINE == 0 {
+        write!(w, "\n  ")?;
+      }
+      let sign = if coefficient < 0.0 { '-' } else { '+' };
+      write!(
+        w,
+        " {} {} {}",
+        sign,
+        coefficient.abs(),
+        self.variables[variable].name
+      )?;
+    }
+    Ok(())
+  }
+
+  /// Writes the model in the CPLEX LP format
+  pub fn write_lp(&self, w: &mut impl Write) -> io::Result<()> {
+    writeln!(w, "\\ {}", self.name)?;
+    write!(w, "Minimize\n obj:")?;
+    self.write_terms(w, &self.objective)?;
+    writeln!(w, "\nSubject To")?;
+    for constraint in &self.constraints {
+      write!(w, " {}:", constraint.name)?;
+      self.write_terms(w, &constraint.terms)?;
+      let sense = match constraint.sense {
+        Sense::Equal => "=",
+        Sense::GreaterOrEqual => ">=",
+        Sense::LessOrEqual => "<=",
+      };
+      writeln!(w, " {} {}", sense, constraint.rhs)?;
+    }
+    writeln!(w, "Bounds")?;
+    for variable in &self.variables {
+      if variable.lower == variable.upper {
+        writeln!(w, " {} = {}", variable.name, variable.lower)?;
+      } else if !variable.binary {
+        writeln!(
+          w,
+          " {} <= {} <= {}",
+          variable.lower, variable.name, variable.upper
+        )?;
+      }
+    }
+    writeln!(w, "Binaries")?;
+    for variable in self.variables.iter().filter(|v| v.binary) {
+      writeln!(w, " {}", variable.name)?;
+    }
+    writeln!(w, "End")
+  }
+
+  /// Writes the model in the free MPS format
+  pub fn write_mps(&self, w: &mut impl Write) -> io::Result<()> {
+    writeln!(w, "NAME {}", self.name.replace(char::is_whitespace, "_"))?;
+    writeln!(w, "ROWS")?;
+    writeln!(w, " N obj")?;
+    for constraint in &self.constraints {
+      let sense = match constraint.sense {
+        Sense::Equal => 'E',
+        Sense::GreaterOrEqual => 'G',
+        Sense::LessOrEqual => 'L',
+      };
+      writeln!(w, " {} {}", sense, constraint.name)?;
+    }
+
+    // Entries by variable, as MPS lists the matrix column by column
+    let mut columns: Vec<Vec<(&str, f64)>> = vec![Vec::new(); self.variables.len()];
+    for &(variable, coefficient) in &self.objective {
+      columns[variable].push(("obj", coefficient));
+    }
+    for constraint in &self.constraints {
+      for &(variable, coefficient) in &constraint.terms {
+        columns[variable].push((&constraint.name, coefficient));
+      }
+    }
+    writeln!(w, "COLUMNS")?;
+    let mut integer = false;
+    for (variable, entries) in self.variables.iter().zip(&columns) {
+      if variable.binary != integer {
+        let marker = if variable.binary { "INTORG" } else { "INTEND" };
+        writeln!(w, " MARKER 'MARKER' '{}'", marker)?;
+        integer = variable.binary;
+      }
+      if entries.is_empty() {
+        // Columns only exist through their entries
+        writeln!(w, " {} obj 0", variable.name)?;
+      }
+      for (row, coefficient) in entries {
+        writeln!(w, " {} {} {}", variable.name, row, coefficient)?;
+      }
+    }
+    if integer {
+      writeln!(w, " MARKER 'MARKER' 'INTEND'")?;
+    }
+
+    writeln!(w, "RHS")?;
+    for constraint in self.constraints.iter().filter(|c| c.rhs != 0.0) {
+      writeln!(w, " rhs {} {}", constraint.name, constraint.rhs)?;
+    }
+    writeln!(w, "BOUNDS")?;
+    for variable in &self.variables {
+      if variable.binary && variable.lower == 0.0 && variable.upper == 1.0 {
+        writeln!(w, " BV bnd {}", variable.name)?;
+      } else {
+        writeln!(w, " LO bnd {} {}", variable.name, variable.lower)?;
+        writeln!(w, " UP bnd {} {}", variable.name, variable.upper)?;
+      }
+    }
+    writeln!(w, "ENDATA")
+  }
+}
+
+fn landing_time_variable(plane_id: ID) -> String {
+  format!("x_{}", plane_id)
+}
+
+fn runway_variable(plane_id: ID, runway: usize) -> String {
+  format!("y_{}_{}", plane_id, runway)
+}
+
+impl LandingProblem {
+  /// Beasley's formulation: landing times `x_i` split into earliness `a_i` and
+  /// tardiness `b_i`, and binaries `d_i_j` for `i` landing before `j`, for
+  /// the pairs whose order the windows do not settle, with big-M separation
+  /// constraints. With several runways, binaries `y_i_r` put `i` on runway
+  /// `r` and `z_i_j` put `i` and `j` on the same one. Frozen arrivals are
+  /// fixed
+  pub fn mip_model(&self) -> Model {
+    let mut model = Model {
+      name: format!(
+        "Airplane landing problem, {} planes, {} runways",
+        self.scheduled_planes().count(),
+        self.runways
+      ),
+      variables: Vec::new(),
+      objective: Vec::new(),
+      constraints: Vec::new(),
+    };
+    let planes: Vec<ID> = self.scheduled_planes().map(|plane| plane.id).collect();
+    let multi_runway = self.runways > 1;
+
+    let mut x = HashMap::new();
+    for &i in &planes {
+      let plane = &self.planes[i];
+      let window = self.landing_window(i);
+      let (earliest, latest) = (*window.start() as f64, *window.end() as f64);
+      let target = plane.target_landing as f64;
+      let x_i = model.variable(landing_time_variable(i), earliest, latest, false);
+      let a_i = model.variable(format!("a_{}", i), 0.0, (target - earliest).max(0.0), false);
+      let b_i = model.variable(format!("b_{}", i), 0.0, (latest - target).max(0.0), false);
+      model.objective.push((a_i, plane.penalty_before));
+      model.objective.push((b_i, plane.penalty_after));
+      model.constrain(
+        format!("target_{}", i),
+        vec![(x_i, 1.0), (a_i, 1.0), (b_i, -1.0)],
+        Sense::Equal,
+        target,
+      );
+      x.insert(i, x_i);
+    }
+
+    let mut y = HashMap::new();
+    if multi_runway {
+      for &i in &planes {
+        let frozen = self.frozen(i).map(|arrival| arrival.runway);
+        let terms = (0..self.runways)
+          .map(|r| {
+            let fixed = frozen.map(|runway| (runway == r) as u8 as f64);
+            let (lower, upper) = fixed.map_or((0.0, 1.0), |value| (value, value));
+            let y_ir = model.variable(runway_variable(i, r), lower, upper, true);
+            y.insert((i, r), y_ir);
+            (y_ir, 1.0)
+          })
+          .collect();
+        model.constrain(format!("runway_{}", i), terms, Sense::Equal, 1.0);
+      }
+    }
+
+    for (k, &i) in planes.iter().enumerate() {
+      for &j in &planes[k + 1..] {
+        let (window_i, window_j) = (self.landing_window(i), self.landing_window(j));
+        let (e_i, l_i) = (*window_i.start() as f64, *window_i.end() as f64);
+        let (e_j, l_j) = (*window_j.start() as f64, *window_j.end() as f64);
+        // Separations on the same runway and on different ones
+        let same = |a: ID, b: ID| self.separation_time_between(a, b) as f64;
+        let cross = |a: ID, b: ID| {
+          self
+            .cross_runway_separation
+            .as_ref()
+            .map_or(0.0, |separation_times| separation_times[a][b] as f64)
+        };
+
+        let z = multi_runway.then(|| {
+          let z_ij = model.variable(format!("z_{}_{}", i, j), 0.0, 1.0, true);
+          for r in 0..self.runways {
+            let (y_ir, y_jr) = (y[&(i, r)], y[&(j, r)]);
+            model.constrain(
+              format!("same_{}_{}_{}", i, j, r),
+              vec![(z_ij, 1.0), (y_ir, -1.0), (y_jr, -1.0)],
+              Sense::GreaterOrEqual,
+              -1.0,
+            );
+            if self.cross_runway_separation.is_some() {
+              // Otherwise landing on the same runway is never less constrained
+              model.constrain(
+                format!("apart_{}_{}_{}", i, j, r),
+                vec![(z_ij, 1.0), (y_ir, 1.0), (y_jr, -1.0)],
+                Sense::LessOrEqual,
+                1.0,
+              );
+            }
+          }
+          z_ij
+        });
+        // `b` lands at least `same(a, b) z + cross(a, b) (1 - z)` after `a`.
+        // With an order binary `d`, and whether `d = 1` means that `a` is
+        // first, the constraint is relaxed by a big-M otherwise
+        let mut separate = |model: &mut Model, a: ID, b: ID, order: Option<(usize, bool)>| {
+          let s = same(a, b);
+          let c = if multi_runway { cross(a, b) } else { s };
+          let latest_a = *self.landing_window(a).end() as f64;
+          let earliest_b = *self.landing_window(b).start() as f64;
+          let big_m = latest_a + s.max(c) - earliest_b;
+          if big_m <= 0.0 {
+            return;
+          }
+          let mut terms = vec![(x[&b], 1.0), (x[&a], -1.0)];
+          if let Some(z_ab) = z {
+            terms.push((z_ab, c - s));
+          }
+          let mut rhs = c;
+          match order {
+            None => {}
+            Some((d, true)) => {
+              terms.push((d, -big_m));
+              rhs -= big_m;
+            }
+            Some((d, false)) => terms.push((d, big_m)),
+          }
+          model.constrain(
+            format!("sep_{}_{}", a, b),
+            terms,
+            Sense::GreaterOrEqual,
+            rhs,
+          );
+        };
+
+        if l_i < e_j {
+          separate(&mut model, i, j, None);
+        } else if l_j < e_i {
+          separate(&mut model, j, i, None);
+        } else {
+          let d_ij = model.variable(format!("d_{}_{}", i, j), 0.0, 1.0, true);
+          separate(&mut model, i, j, Some((d_ij, true)));
+          separate(&mut model, j, i, Some((d_ij, false)));
+        }
+      }
+    }
+    model
+  }
+
+  /// Reads the landing times, and the runways, of a solution of
+  /// `mip_model` from a solver's solution file. Any line holding a variable
+  /// name followed by its value is read, as well as `name="..." value="..."`
+  /// attributes, which covers the CPLEX, Gurobi, CBC, SCIP, HiGHS and GLPK
+  /// outputs
+  pub fn read_mip_solution(&self, reader: impl BufRead) -> Result<Solution, SolutionError> {
+    let mut values: HashMap<String, f64> = HashMap::new();
+    for line in reader.lines() {
+      let line = line?;
+      if let (Some(name), Some(value)) = (attribute(&line, "name"), attribute(&line, "value")) {
+        if let Ok(value) = value.parse() {
+          values.insert(name.to_string(), value);
+        }
+        continue;
+      }
+      let tokens: Vec<&str> = line.split_whitespace().collect();
+      for (k, token) in tokens.iter().enumerate() {
+        let is_variable = token.starts_with("x_") || token.starts_with("y_");
+        if let (true, Some(value)) = (is_variable, tokens.get(k + 1)) {
+          if let Ok(value) = value.parse() {
+            values.insert(token.to_string(), value);
+          }
+          break;
+        }
+      }
+    }
+
+    let mut solution: Solution = self
+      .scheduled_planes()
+      .map(|plane| {
+        let name = landing_time_variable(plane.id);
+        let time = *values
+          .get(&name)
+          .ok_or(SolutionError::Missing(name.clone()))?;
+        if time < 0.0 {
+          return Err(SolutionError::Value { name, value: time });
+        }
+        // Solvers omit variables at zero
+        let runway = (0..self.runways)
+          .find(|&r| {
+            values
+              .get(&runway_variable(plane.id, r))
+              .copied()
+              .unwrap_or(0.0)
+              > 0.5
+          })
+          .unwrap_or(0);
+        Ok(Arrival::on_runway(plane.id, time.round() as u32, runway))
+      })
+      .try_collect()?;
+    solution.sort_by_key(|a| a.landing_time);
+    Ok(solution)
+  }
+}
+
+/// Value of `key="..."` in `line`
+fn attribute<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+  let pattern = format!("{}=\"", key);
+  let start = line
+    .match_indices(&pattern)
+    .find(|(k, _)| *k == 0 || line[..*k].ends_with(char::is_whitespace))?
+    .0
+    + pattern.len();
+  let end = line[start..].find('"')?;
+  Some(&line[start..start + end])
+}
+
+#[derive(Debug)]
+pub enum SolutionError {
+  Io(io::Error),
+  /// No value for the landing time variable of a plane
+  Missing(String),
+  Value {
+    name: String,
+    value: f64,
+  },
+}
+
+impl fmt::Display for SolutionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SolutionError::Io(e) => write!(f, "I/O error: {}", e),
+      SolutionError::Missing(name) => write!(f, "no value for {}", name),
+      SolutionError::Value { name, value } => write!(f, "invalid value {} for {}", value, name),
+    }
+  }
+}
+
+impl error::Error for SolutionError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      SolutionError::Io(e) => Some(e),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for SolutionError {
+  fn from(e: io::Error) -> Self {
+    SolutionError::Io(e)
+  }
+}
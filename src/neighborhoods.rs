@@ -0,0 +1,308 @@
+use crate::metaheuristics::{IncrementalProblem, Neighborhoods, Problem};
+use crate::permutation::MAX_BLOCK_LENGTH;
+use crate::problem::{Arrival, ArrivalMove, LandingProblem, Solution, CONFLICT_PENALTY};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Smallest cost change counted as an improvement, as a neighbor can make
+/// several moves whose deltas do not add up exactly
+const EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrivalNeighborhood {
+  /// Lands one plane at another time
+  ShiftTime,
+  /// Exchanges the landing times and runways of two consecutive arrivals
+  SwapAdjacent,
+  /// Exchanges the landing times and runways of any two arrivals
+  SwapAny,
+  /// Shifts a short block of consecutive arrivals by one unit of time, or
+  /// until one of them lands at its target time if that is within the largest
+  /// separation time
+  BlockMove,
+}
+
+impl ArrivalNeighborhood {
+  pub const ALL: [ArrivalNeighborhood; 4] = [
+    ArrivalNeighborhood::ShiftTime,
+    ArrivalNeighborhood::SwapAdjacent,
+    ArrivalNeighborhood::SwapAny,
+    ArrivalNeighborhood::BlockMove,
+  ];
+}
+
+impl LandingProblem {
+  /// Move landing the plane of `arrival` as `arrival` does
+  fn relocation(solution: &Solution, arrival: &Arrival) -> ArrivalMove {
+    ArrivalMove {
+      index: solution
+        .iter()
+        .position(|a| a.plane_id == arrival.plane_id)
+        .unwrap(),
+      landing_time: arrival.landing_time,
+      runway: arrival.runway,
+    }
+  }
+
+  /// Lands the planes of `relocated` as given there, returns the cost change
+  fn relocate(&self, solution: &mut Solution, relocated: &[Arrival]) -> f64 {
+    relocated
+      .iter()
+      .map(|arrival| {
+        let m = Self::relocation(solution, arrival);
+        let delta = self.move_delta(solution, &m);
+        self.apply_move(solution, m);
+        delta
+      })
+      .sum()
+  }
+
+  /// `a` and `b` exchanging their landing times and runways. `None` if either
+  /// is frozen or cannot land at the time of the other
+  fn swap(&self, a: &Arrival, b: &Arrival) -> Option<Vec<Arrival>> {
+    let can_land = |arrival: &Arrival, other: &Arrival| {
+      self.frozen(arrival.plane_id).is_none()
+        && self
+          .landing_window(arrival.plane_id)
+          .contains(&other.landing_time)
+    };
+    if !can_land(a, b) || !can_land(b, a) {
+      return None;
+    }
+    Some(vec![
+      Arrival::on_runway(a.plane_id, b.landing_time, b.runway),
+      Arrival::on_runway(b.plane_id, a.landing_time, a.runway),
+    ])
+  }
+
+  /// Cheapest time for the arrival at `index` to land at, the other arrivals
+  /// staying where they are. The cost at every time of the window is summed
+  /// up from the arrivals within separation range of it
+  fn best_landing_time(&self, solution: &Solution, index: usize) -> u32 {
+    let arrival = solution[index];
+    let plane = &self.planes[arrival.plane_id];
+    let window = self.landing_window(arrival.plane_id);
+    let (earliest, latest) = (*window.start(), *window.end());
+    let mut costs: Vec<f64> = window.map(|time| plane.cost_for_landing(time)).collect();
+    let at = |time: u32| Arrival::on_runway(arrival.plane_id, time, arrival.runway);
+
+    let first = solution.partition_point(|b| b.landing_time + self.max_separation <= earliest);
+    let others = solution[first..]
+      .iter()
+      .enumerate()
+      .filter(|&(i, _)| first + i != index)
+      .map(|(_, b)| b)
+      .take_while(|b| b.landing_time < latest + self.max_separation);
+    for b in others {
+      // Landing before `b`, then at the same time or after it
+      let before = b
+        .landing_time
+        .saturating_sub(self.max_separation)
+        .max(earliest);
+      for time in before..b.landing_time.min(latest + 1) {
+        let conflict = self.conflict_between(&at(time), b);
+        costs[(time - earliest) as usize] += CONFLICT_PENALTY * conflict as f64;
+      }
+      let after = (b.landing_time + self.max_separation).min(latest + 1);
+      for time in b.landing_time.max(earliest)..after {
+        let conflict = self.conflict_between(b, &at(time));
+        costs[(time - earliest) as usize] += CONFLICT_PENALTY * conflict as f64;
+      }
+    }
+
+    let best = (0..costs.len())
+      .min_by(|&i, &j| costs[i].partial_cmp(&costs[j]).unwrap())
+      .unwrap();
+    // Ties keep the arrival where it is
+    let current = (arrival.landing_time - earliest) as usize;
+    if costs[best] < costs[current] {
+      earliest + best as u32
+    } else {
+      arrival.landing_time
+    }
+  }
+
+  /// Shifts of `block` in the block-move neighborhood
+  fn block_shifts(&self, block: &[Arrival]) -> Vec<i64> {
+    let (earliest, latest) = self.shift_bounds(block);
+    let mut shifts: Vec<i64> = block
+      .iter()
+      .map(|arrival| {
+        let target = self.planes[arrival.plane_id].target_landing;
+        target as i64 - arrival.landing_time as i64
+      })
+      .chain([-1, 1])
+      .filter(|&shift| shift != 0 && (earliest..=latest).contains(&shift))
+      .collect();
+    shifts.sort_by_key(|&shift| (shift.abs(), shift));
+    shifts.dedup();
+    shifts
+  }
+
+  /// Shifts by which `block` stays within its landing windows and the largest
+  /// separation time
+  fn shift_bounds(&self, block: &[Arrival]) -> (i64, i64) {
+    let max_shift = self.max_separation as i64;
+    block
+      .iter()
+      .fold((-max_shift, max_shift), |(earliest, latest), arrival| {
+        let window = self.landing_window(arrival.plane_id);
+        let time = arrival.landing_time as i64;
+        (
+          earliest.max(*window.start() as i64 - time),
+          latest.min(*window.end() as i64 - time),
+        )
+      })
+  }
+
+  fn shift_block(block: &[Arrival], shift: i64) -> Vec<Arrival> {
+    block
+      .iter()
+      .map(|arrival| {
+        let time = (arrival.landing_time as i64 + shift) as u32;
+        Arrival::on_runway(arrival.plane_id, time, arrival.runway)
+      })
+      .collect()
+  }
+
+  /// Neighbors in `neighborhood` changing the arrival at `index` first, as
+  /// the arrivals they relocate
+  fn relocations_from(
+    &self,
+    neighborhood: ArrivalNeighborhood,
+    solution: &Solution,
+    index: usize,
+  ) -> Vec<Vec<Arrival>> {
+    let arrival = solution[index];
+    match neighborhood {
+      ArrivalNeighborhood::ShiftTime => {
+        let time = self.best_landing_time(solution, index);
+        if time == arrival.landing_time {
+          return Vec::new();
+        }
+        vec![vec![Arrival::on_runway(
+          arrival.plane_id,
+          time,
+          arrival.runway,
+        )]]
+      }
+      ArrivalNeighborhood::SwapAdjacent => solution
+        .get(index + 1)
+        .and_then(|next| self.swap(&arrival, next))
+        .into_iter()
+        .collect(),
+      ArrivalNeighborhood::SwapAny => solution[index + 1..]
+        .iter()
+        .filter_map(|other| self.swap(&arrival, other))
+        .collect(),
+      ArrivalNeighborhood::BlockMove => (2..=MAX_BLOCK_LENGTH)
+        .filter(|length| index + length <= solution.len())
+        .flat_map(|length| {
+          let block = &solution[index..index + length];
+          let shifts = self.block_shifts(block);
+          shifts
+            .into_iter()
+            .map(|shift| Self::shift_block(block, shift))
+        })
+        .collect(),
+    }
+  }
+
+  fn random_relocation<R: Rng + ?Sized>(
+    &self,
+    neighborhood: ArrivalNeighborhood,
+    solution: &Solution,
+    rng: &mut R,
+  ) -> Vec<Arrival> {
+    let n = solution.len();
+    match neighborhood {
+      ArrivalNeighborhood::ShiftTime if n > 0 => {
+        let arrival = solution.choose(rng).unwrap();
+        let time = rng.gen_range(self.landing_window(arrival.plane_id));
+        vec![Arrival::on_runway(arrival.plane_id, time, arrival.runway)]
+      }
+      ArrivalNeighborhood::SwapAdjacent if n > 1 => {
+        let i = rng.gen_range(0..n - 1);
+        self
+          .swap(&solution[i], &solution[i + 1])
+          .unwrap_or_default()
+      }
+      ArrivalNeighborhood::SwapAny if n > 0 => {
+        let arrival = solution.choose(rng).unwrap();
+        let swaps: Vec<_> = solution
+          .iter()
+          .filter(|other| other.plane_id != arrival.plane_id)
+          .filter_map(|other| self.swap(arrival, other))
+          .collect();
+        swaps.choose(rng).cloned().unwrap_or_default()
+      }
+      ArrivalNeighborhood::BlockMove if n > 1 => {
+        let length = rng.gen_range(2..=MAX_BLOCK_LENGTH.min(n));
+        let start = rng.gen_range(0..=n - length);
+        let block = &solution[start..start + length];
+        match self.block_shifts(block).choose(rng) {
+          Some(&shift) => Self::shift_block(block, shift),
+          None => Vec::new(),
+        }
+      }
+      _ => Vec::new(),
+    }
+  }
+}
+
+/// Shift-time, swap-adjacent, swap-any and block-move, in that order
+impl Neighborhoods<Solution> for LandingProblem {
+  type Neighborhood = ArrivalNeighborhood;
+
+  fn neighborhoods(&self) -> Vec<ArrivalNeighborhood> {
+    ArrivalNeighborhood::ALL.to_vec()
+  }
+
+  fn random_neighbor_in<R: Rng + ?Sized>(
+    &self,
+    neighborhood: ArrivalNeighborhood,
+    solution: &Solution,
+    rng: &mut R,
+  ) -> Solution {
+    let mut new_solution = solution.clone();
+    if !solution.is_empty() {
+      let relocated = self.random_relocation(neighborhood, solution, rng);
+      self.relocate(&mut new_solution, &relocated);
+    }
+    new_solution
+  }
+
+  /// Best neighbor changing the first arrival, taken in random order, that
+  /// has a neighbor with a lower cost. Going straight to the best time of a
+  /// plane saves many small steps towards it
+  fn first_improvement_in<R: Rng + ?Sized>(
+    &self,
+    neighborhood: ArrivalNeighborhood,
+    solution: &Solution,
+    rng: &mut R,
+  ) -> Option<Solution> {
+    let mut indices: Vec<usize> = (0..solution.len()).collect();
+    indices.shuffle(rng);
+    let mut new_solution = solution.clone();
+    for index in indices {
+      let mut best: Option<(Vec<Arrival>, f64)> = None;
+      for relocated in self.relocations_from(neighborhood, solution, index) {
+        // Only the last arrival is evaluated without being moved
+        let (last, first) = relocated.split_last().unwrap();
+        let delta = self.relocate(&mut new_solution, first)
+          + self.move_delta(&new_solution, &Self::relocation(&new_solution, last));
+        if !first.is_empty() {
+          new_solution.copy_from_slice(solution);
+        }
+        if delta < best.as_ref().map_or(-EPSILON, |(_, best)| *best) {
+          best = Some((relocated, delta));
+        }
+      }
+      if let Some((relocated, _)) = best {
+        self.relocate(&mut new_solution, &relocated);
+        return Some(new_solution);
+      }
+    }
+    None
+  }
+}
@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Runs `job(0)` to `job(jobs - 1)` on `workers` threads, each taking the
+/// next job left. Results are in job order, whatever the number of workers
+pub fn run_parallel<T, F>(jobs: usize, workers: usize, job: F) -> Vec<T>
+where
+  T: Send,
+  F: Fn(usize) -> T + Sync,
+{
+  let next = AtomicUsize::new(0);
+  let results: Mutex<Vec<Option<T>>> = Mutex::new((0..jobs).map(|_| None).collect());
+  thread::scope(|scope| {
+    for _ in 0..workers.clamp(1, jobs.max(1)) {
+      scope.spawn(|| loop {
+        let k = next.fetch_add(1, Ordering::Relaxed);
+        if k >= jobs {
+          break;
+        }
+        let result = job(k);
+        results.lock().unwrap()[k] = Some(result);
+      });
+    }
+  });
+  results
+    .into_inner()
+    .unwrap()
+    .into_iter()
+    .map(Option::unwrap)
+    .collect()
+}
+
+/// Number of threads the machine can run at once
+pub fn available_workers() -> usize {
+  thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Seed of the job `job` of a batch seeded with `seed`, mixed with SplitMix64
+/// so that nearby jobs get unrelated seeds
+pub fn job_seed(seed: u64, job: u64) -> u64 {
+  let mut z = seed.wrapping_add(job.wrapping_add(1).wrapping_mul(0x9e3779b97f4a7c15));
+  z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+  z ^ (z >> 31)
+}
+
+/// Mean, best, worst and sample standard deviation of costs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+  pub mean: f64,
+  pub best: f64,
+  pub worst: f64,
+  pub std_dev: f64,
+}
+
+impl Summary {
+  pub fn of(costs: &[f64]) -> Self {
+    let n = costs.len() as f64;
+    let mean = costs.iter().sum::<f64>() / n;
+    let variance = if costs.len() > 1 {
+      costs.iter().map(|cost| (cost - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    } else {
+      0.0
+    };
+    Summary {
+      mean,
+      best: costs.iter().copied().fold(f64::INFINITY, f64::min),
+      worst: costs.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+      std_dev: variance.sqrt(),
+    }
+  }
+}
@@ -1,8 +1,13 @@
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::{error, fmt};
 
 #[derive(Debug, Clone)]
 pub struct Plane {
+  /// When the plane shows up to the scheduler
+  pub appearance_time: u32,
   pub earliest_landing: u32,
   pub target_landing: u32,
   pub latest_landing: u32,
@@ -16,47 +21,266 @@ pub struct Plane {
 #[derive(Debug)]
 pub struct ProblemData {
   pub num_planes: usize,
+  /// How long before landing a schedule can no longer change
+  pub freeze_time: u32,
   pub planes: Vec<Plane>,
+  /// Malformed separation times read as 0 in lenient mode, as the errors of
+  /// strict mode
+  pub replaced: Vec<ParseError>,
 }
 
-pub fn parse_problem_data(file_path: &str) -> io::Result<ProblemData> {
-  let mut lines = io::BufReader::new(File::open(file_path)?).lines();
+/// Separation times per line in the OR-Library files
+const SEPARATIONS_PER_LINE: usize = 8;
+
+impl ProblemData {
+  /// Writes the instance in the OR-Library format `parse_problem` reads
+  pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+    writeln!(writer, " {} {} ", self.num_planes, self.freeze_time)?;
+    for plane in &self.planes {
+      writeln!(
+        writer,
+        " {} {} {} {} {:.2} {:.2} ",
+        plane.appearance_time,
+        plane.earliest_landing,
+        plane.target_landing,
+        plane.latest_landing,
+        plane.penalty_before,
+        plane.penalty_after
+      )?;
+      for times in plane.separation_times.chunks(SEPARATIONS_PER_LINE) {
+        for time in times {
+          write!(writer, " {}", time)?;
+        }
+        writeln!(writer, " ")?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+  /// Malformed separation times are read as 0, as the OR-Library files have
+  /// always been loaded, and listed in `ProblemData::replaced`
+  #[default]
+  Lenient,
+  /// Every malformed number is an error, and so is a target landing time
+  /// outside of the landing window
+  Strict,
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+  Io(io::Error),
+  Format {
+    /// 1-based line of the offending token, or of the end of the file
+    line: usize,
+    /// 1-based column of the offending token
+    column: usize,
+    /// Index of the plane being read, if past the first line
+    plane: Option<usize>,
+    expected: &'static str,
+    /// Offending token, `None` at the end of the file
+    found: Option<String>,
+  },
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParseError::Io(e) => write!(f, "I/O error: {}", e),
+      ParseError::Format {
+        line,
+        column,
+        plane,
+        expected,
+        found,
+      } => {
+        write!(f, "line {}, column {}", line, column)?;
+        if let Some(plane) = plane {
+          write!(f, " (plane {})", plane)?;
+        }
+        write!(f, ": expected {}, ", expected)?;
+        match found {
+          Some(token) => write!(f, "found {:?}", token),
+          None => write!(f, "found end of file"),
+        }
+      }
+    }
+  }
+}
+
+impl error::Error for ParseError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      ParseError::Io(e) => Some(e),
+      ParseError::Format { .. } => None,
+    }
+  }
+}
+
+impl From<io::Error> for ParseError {
+  fn from(e: io::Error) -> Self {
+    ParseError::Io(e)
+  }
+}
+
+struct Token {
+  line: usize,
+  column: usize,
+  text: String,
+}
+
+/// Whitespace separated tokens of the input, with their position
+struct Tokens<R> {
+  lines: io::Lines<R>,
+  line: usize,
+  pending: VecDeque<Token>,
+  plane: Option<usize>,
+}
+
+impl<R: BufRead> Tokens<R> {
+  fn new(reader: R) -> Self {
+    Tokens {
+      lines: reader.lines(),
+      line: 0,
+      pending: VecDeque::new(),
+      plane: None,
+    }
+  }
+
+  fn next(&mut self, expected: &'static str) -> Result<Token, ParseError> {
+    while self.pending.is_empty() {
+      let Some(line) = self.lines.next() else {
+        return Err(ParseError::Format {
+          line: self.line,
+          column: 1,
+          plane: self.plane,
+          expected,
+          found: None,
+        });
+      };
+      let line = line?;
+      self.line += 1;
+      self
+        .pending
+        .extend(line.split_whitespace().map(|text| Token {
+          line: self.line,
+          column: text.as_ptr() as usize - line.as_ptr() as usize + 1,
+          text: text.to_string(),
+        }));
+    }
+    Ok(self.pending.pop_front().unwrap())
+  }
+
+  fn error(&self, token: Token, expected: &'static str) -> ParseError {
+    ParseError::Format {
+      line: token.line,
+      column: token.column,
+      plane: self.plane,
+      expected,
+      found: Some(token.text),
+    }
+  }
+
+  fn number<T: FromStr>(&mut self, expected: &'static str) -> Result<T, ParseError> {
+    let token = self.next(expected)?;
+    token.text.parse().map_err(|_| self.error(token, expected))
+  }
+
+  /// `number`, with its token for the errors found once other numbers are read
+  fn located_number<T: FromStr>(
+    &mut self,
+    expected: &'static str,
+  ) -> Result<(T, Token), ParseError> {
+    let token = self.next(expected)?;
+    match token.text.parse() {
+      Ok(value) => Ok((value, token)),
+      Err(_) => Err(self.error(token, expected)),
+    }
+  }
+
+  /// A row of `planes` separation times, the malformed ones pushed to
+  /// `replaced` and read as 0 in lenient mode
+  fn separation_times(
+    &mut self,
+    planes: usize,
+    mode: ParseMode,
+    replaced: &mut Vec<ParseError>,
+  ) -> Result<Vec<u32>, ParseError> {
+    let mut separation_times = Vec::new();
+    while separation_times.len() < planes {
+      let separation_time = match mode {
+        ParseMode::Strict => self.number("separation time")?,
+        ParseMode::Lenient => {
+          let token = self.next("separation time")?;
+          token.text.parse::<u32>().unwrap_or_else(|_| {
+            replaced.push(self.error(token, "separation time"));
+            0
+          })
+        }
+      };
+      separation_times.push(separation_time);
+    }
+    Ok(separation_times)
+  }
+
+  /// Fails on any token left
+  fn end(&mut self) -> Result<(), ParseError> {
+    self.plane = None;
+    match self.next("end of file") {
+      Ok(token) => Err(self.error(token, "end of file")),
+      Err(ParseError::Format { found: None, .. }) => Ok(()),
+      Err(e) => Err(e),
+    }
+  }
+}
+
+pub fn parse_problem_data(file_path: &str) -> Result<ProblemData, ParseError> {
+  parse_problem_data_with(file_path, ParseMode::default())
+}
+
+pub fn parse_problem_data_with(
+  file_path: &str,
+  mode: ParseMode,
+) -> Result<ProblemData, ParseError> {
+  parse_problem(io::BufReader::new(File::open(file_path)?), mode)
+}
+
+pub fn parse_problem<R: BufRead>(reader: R, mode: ParseMode) -> Result<ProblemData, ParseError> {
+  let mut tokens = Tokens::new(reader);
 
   // First line: number of planes and freeze time
-  let first_line = lines.next().unwrap()?;
-  let mut parts = first_line.split_whitespace();
-  let num_planes: usize = parts.next().unwrap().parse().unwrap();
-  let _freeze_time: u32 = parts.next().unwrap().parse().unwrap();
+  let num_planes: usize = tokens.number("number of planes")?;
+  let freeze_time: u32 = tokens.number("freeze time")?;
 
-  let mut planes = Vec::with_capacity(num_planes);
+  // Not preallocated, as the number of planes might not be the one of the
+  // file
+  let mut planes = Vec::new();
+  let mut replaced = Vec::new();
 
-  for _ in 0..num_planes {
+  for i in 0..num_planes {
+    tokens.plane = Some(i);
     // Parse the plane's landing details
-    let plane_line = lines.next().unwrap()?;
-    let mut parts = plane_line.split_whitespace();
-    let _appearance_time: u32 = parts.next().unwrap().parse().unwrap();
-    let earliest_landing: u32 = parts.next().unwrap().parse().unwrap();
-    let target_landing: u32 = parts.next().unwrap().parse().unwrap();
-    let latest_landing: u32 = parts.next().unwrap().parse().unwrap();
-    let penalty_before: f64 = parts.next().unwrap().parse().unwrap();
-    let penalty_after: f64 = parts.next().unwrap().parse().unwrap();
-
-    // Parse the separation times for this plane
-    let mut separation_times = Vec::with_capacity(num_planes);
-    while separation_times.len() < num_planes {
-      let sep_line = lines.next().unwrap()?;
-      separation_times.extend(
-        sep_line
-          .split_whitespace()
-          .map(|s| s.parse::<u32>().unwrap_or_default()),
-      );
-      // println!("{} {}", separation_times.len(), num_planes);
+    let appearance_time: u32 = tokens.number("appearance time")?;
+    let earliest_landing: u32 = tokens.number("earliest landing time")?;
+    let (target_landing, target): (u32, _) = tokens.located_number("target landing time")?;
+    let (latest_landing, latest): (u32, _) = tokens.located_number("latest landing time")?;
+    if latest_landing < earliest_landing {
+      return Err(tokens.error(latest, "latest landing time not before the earliest one"));
+    }
+    let window = earliest_landing..=latest_landing;
+    if mode == ParseMode::Strict && !window.contains(&target_landing) {
+      return Err(tokens.error(target, "target landing time within the landing window"));
     }
+    let penalty_before: f64 = tokens.number("penalty before target")?;
+    let penalty_after: f64 = tokens.number("penalty after target")?;
 
-    // // It is the subsequent lines of the separation times
-    // lines.next();
+    // Parse the separation times for this plane
+    let separation_times = tokens.separation_times(num_planes, mode, &mut replaced)?;
 
     planes.push(Plane {
+      appearance_time,
       earliest_landing,
       target_landing,
       latest_landing,
@@ -68,5 +292,153 @@ pub fn parse_problem_data(file_path: &str) -> io::Result<ProblemData> {
 
   // planes.sort_by(|a, b| a.target_landing.cmp(&b.target_landing));
 
-  Ok(ProblemData { num_planes, planes })
+  Ok(ProblemData {
+    num_planes,
+    freeze_time,
+    planes,
+    replaced,
+  })
+}
+
+/// Separation times between planes landing on different runways, which the
+/// OR-Library files leave out
+#[derive(Debug)]
+pub struct SeparationMatrix {
+  /// Indexed like `Plane::separation_times`
+  pub separation_times: Vec<Vec<u32>>,
+  /// Malformed separation times read as 0 in lenient mode, as the errors of
+  /// strict mode
+  pub replaced: Vec<ParseError>,
+}
+
+pub fn parse_separation_matrix_from(
+  file_path: &str,
+  planes: usize,
+  mode: ParseMode,
+) -> Result<SeparationMatrix, ParseError> {
+  parse_separation_matrix(io::BufReader::new(File::open(file_path)?), planes, mode)
+}
+
+/// Reads `planes` rows of `planes` separation times, laid out as the ones of
+/// the instance files, and nothing else, so that a matrix of another instance
+/// is rejected
+pub fn parse_separation_matrix<R: BufRead>(
+  reader: R,
+  planes: usize,
+  mode: ParseMode,
+) -> Result<SeparationMatrix, ParseError> {
+  let mut tokens = Tokens::new(reader);
+  let mut separation_times = Vec::new();
+  let mut replaced = Vec::new();
+  for i in 0..planes {
+    tokens.plane = Some(i);
+    separation_times.push(tokens.separation_times(planes, mode, &mut replaced)?);
+  }
+  tokens.end()?;
+  Ok(SeparationMatrix {
+    separation_times,
+    replaced,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const MALFORMED: &str = "2 10\n0 5 10 20 1.5 2.5\n99999 x3\n0 5 10 20 1.5 2.5\n4 99999\n";
+
+  #[test]
+  fn lenient_mode_lists_replaced_separation_times() {
+    let data = parse_problem(MALFORMED.as_bytes(), ParseMode::Lenient).unwrap();
+    assert_eq!(data.planes[0].separation_times, vec![99999, 0]);
+    assert_eq!(data.planes[1].separation_times, vec![4, 99999]);
+    assert_eq!(data.replaced.len(), 1);
+    assert_eq!(
+      data.replaced[0].to_string(),
+      "line 3, column 7 (plane 0): expected separation time, found \"x3\""
+    );
+  }
+
+  fn format_error(text: &str, mode: ParseMode) -> (usize, usize, &'static str) {
+    match parse_problem(text.as_bytes(), mode) {
+      Err(ParseError::Format {
+        line,
+        column,
+        expected,
+        ..
+      }) => (line, column, expected),
+      result => panic!("{:?}", result),
+    }
+  }
+
+  #[test]
+  fn plane_count_is_not_trusted() {
+    let (line, _, expected) = format_error("99999999999 10\n0 5 10 20 1 1\n", ParseMode::Lenient);
+    assert_eq!((line, expected), (2, "separation time"));
+  }
+
+  #[test]
+  fn rejects_empty_landing_windows() {
+    let text = "1 10\n0 20 15 10 1 1\n99999\n";
+    for mode in [ParseMode::Lenient, ParseMode::Strict] {
+      assert_eq!(
+        format_error(text, mode),
+        (2, 9, "latest landing time not before the earliest one")
+      );
+    }
+  }
+
+  #[test]
+  fn strict_mode_rejects_targets_outside_of_windows() {
+    let text = "1 10\n0 5 30 20 1 1\n99999\n";
+    assert_eq!(
+      format_error(text, ParseMode::Strict),
+      (2, 5, "target landing time within the landing window")
+    );
+    let data = parse_problem(text.as_bytes(), ParseMode::Lenient).unwrap();
+    assert_eq!(data.planes[0].target_landing, 30);
+  }
+
+  #[test]
+  fn strict_mode_rejects_malformed_separation_times() {
+    let e = parse_problem(MALFORMED.as_bytes(), ParseMode::Strict).unwrap_err();
+    assert!(matches!(
+      e,
+      ParseError::Format {
+        line: 3,
+        column: 7,
+        plane: Some(0),
+        ..
+      }
+    ));
+  }
+
+  #[test]
+  fn reads_separation_matrices() {
+    let text = "0 3\n 2 0\n";
+    let matrix = parse_separation_matrix(text.as_bytes(), 2, ParseMode::Strict).unwrap();
+    assert_eq!(matrix.separation_times, vec![vec![0, 3], vec![2, 0]]);
+    assert!(matrix.replaced.is_empty());
+
+    let text = "0 x\n2 0\n";
+    let matrix = parse_separation_matrix(text.as_bytes(), 2, ParseMode::Lenient).unwrap();
+    assert_eq!(matrix.separation_times, vec![vec![0, 0], vec![2, 0]]);
+    assert_eq!(
+      matrix.replaced[0].to_string(),
+      "line 1, column 3 (plane 0): expected separation time, found \"x\""
+    );
+    assert!(parse_separation_matrix(text.as_bytes(), 2, ParseMode::Strict).is_err());
+
+    // Matrices of other instances
+    let e = parse_separation_matrix("0 3\n2 0\n".as_bytes(), 3, ParseMode::Strict).unwrap_err();
+    assert_eq!(
+      e.to_string(),
+      "line 2, column 1 (plane 1): expected separation time, found end of file"
+    );
+    let e = parse_separation_matrix("0 3\n2 0\n".as_bytes(), 1, ParseMode::Strict).unwrap_err();
+    assert_eq!(
+      e.to_string(),
+      "line 1, column 3: expected end of file, found \"3\""
+    );
+  }
 }
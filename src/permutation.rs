@@ -0,0 +1,243 @@
+use crate::metaheuristics::{Neighborhoods, NotIncremental, Problem};
+use crate::problem::{LandingProblem, Solution, ID};
+use crate::timing::InfeasibleOrder;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Landing order of the planes, landing times are decoded with
+/// [`LandingProblem::timing`]
+pub type Permutation = Vec<ID>;
+
+/// Longest block of planes moved at once by [`Neighborhood::BlockMove`]
+pub(crate) const MAX_BLOCK_LENGTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+  /// Exchanges two planes
+  Swap,
+  /// Moves one plane to another position
+  Insertion,
+  /// Moves a short block of consecutive planes to another position
+  BlockMove,
+  /// Reverses the order of a segment of planes
+  TwoOpt,
+}
+
+impl Neighborhood {
+  pub const ALL: [Neighborhood; 4] = [
+    Neighborhood::Swap,
+    Neighborhood::Insertion,
+    Neighborhood::BlockMove,
+    Neighborhood::TwoOpt,
+  ];
+
+  /// Random move over a permutation of `n` planes, `n` must be at least 2
+  pub fn random_move<R: Rng + ?Sized>(self, n: usize, rng: &mut R) -> Move {
+    match self {
+      Neighborhood::Swap => {
+        let [i, j] = distinct_pair(n, rng);
+        Move::Swap(i, j)
+      }
+      Neighborhood::Insertion => {
+        let [from, to] = distinct_pair(n, rng);
+        Move::Insertion(from, to)
+      }
+      Neighborhood::BlockMove => {
+        let length = rng.gen_range(1..=MAX_BLOCK_LENGTH.min(n - 1));
+        let start = rng.gen_range(0..=n - length);
+        // Position of the block among the other planes, different from `start`
+        let mut to = rng.gen_range(0..n - length);
+        if to >= start {
+          to += 1;
+        }
+        if to > start {
+          Move::BlockMove(start, start + length, to + length)
+        } else {
+          Move::BlockMove(to, start, start + length)
+        }
+      }
+      Neighborhood::TwoOpt => {
+        let [i, j] = distinct_pair(n, rng);
+        Move::TwoOpt(i.min(j), i.max(j))
+      }
+    }
+  }
+
+  /// Every move of the neighborhood over a permutation of `n` planes
+  pub fn moves(self, n: usize) -> Vec<Move> {
+    let pairs = (0..n).flat_map(move |i| (i + 1..n).map(move |j| (i, j)));
+    match self {
+      Neighborhood::Swap => pairs.map(|(i, j)| Move::Swap(i, j)).collect(),
+      Neighborhood::Insertion => pairs
+        .flat_map(|(i, j)| [Move::Insertion(i, j), Move::Insertion(j, i)])
+        .collect(),
+      Neighborhood::BlockMove => {
+        let mut moves = Vec::new();
+        for j in 1..n {
+          for i in j.saturating_sub(MAX_BLOCK_LENGTH)..j {
+            // Short block [i, j) moved after any later plane
+            moves.extend((j + 1..=n).map(|k| Move::BlockMove(i, j, k)));
+          }
+          for k in j + 1..=(j + MAX_BLOCK_LENGTH).min(n) {
+            // Short block [j, k) moved before any earlier plane, unless it
+            // was already counted above
+            let earlier = 0..j.saturating_sub(MAX_BLOCK_LENGTH);
+            moves.extend(earlier.map(|i| Move::BlockMove(i, j, k)));
+          }
+        }
+        moves
+      }
+      Neighborhood::TwoOpt => pairs.map(|(i, j)| Move::TwoOpt(i, j)).collect(),
+    }
+  }
+}
+
+fn distinct_pair<R: Rng + ?Sized>(n: usize, rng: &mut R) -> [usize; 2] {
+  let i = rng.gen_range(0..n);
+  let j = rng.gen_range(0..n - 1);
+  [i, if j >= i { j + 1 } else { j }]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+  Swap(usize, usize),
+  /// Plane at the first position moved to the second one
+  Insertion(usize, usize),
+  /// Exchanges the adjacent blocks `i..j` and `j..k`
+  BlockMove(usize, usize, usize),
+  /// Reverses `i..=j`
+  TwoOpt(usize, usize),
+}
+
+impl Move {
+  pub fn apply(self, permutation: &mut Permutation) {
+    match self {
+      Move::Swap(i, j) => permutation.swap(i, j),
+      Move::Insertion(from, to) => {
+        let plane_id = permutation.remove(from);
+        permutation.insert(to, plane_id);
+      }
+      Move::BlockMove(i, j, k) => permutation[i..k].rotate_left(j - i),
+      Move::TwoOpt(i, j) => permutation[i..=j].reverse(),
+    }
+  }
+}
+
+/// Searches over landing orders only, every order being scheduled at its
+/// optimal landing times. Infeasible orders cost their
+/// [`LandingProblem::penalized_cost`]
+pub struct PermutationProblem<'a> {
+  pub problem: &'a LandingProblem,
+  pub neighborhoods: Vec<Neighborhood>,
+}
+
+impl<'a> PermutationProblem<'a> {
+  pub fn new(problem: &'a LandingProblem) -> Self {
+    PermutationProblem {
+      problem,
+      neighborhoods: Neighborhood::ALL.to_vec(),
+    }
+  }
+
+  pub fn with_neighborhoods(mut self, neighborhoods: Vec<Neighborhood>) -> Self {
+    assert!(!neighborhoods.is_empty());
+    self.neighborhoods = neighborhoods;
+    self
+  }
+
+  pub fn to_solution(&self, permutation: &Permutation) -> Result<Solution, InfeasibleOrder> {
+    self.problem.timing(permutation)
+  }
+
+  pub fn from_solution(solution: &Solution) -> Permutation {
+    solution.iter().map(|arrival| arrival.plane_id).collect()
+  }
+}
+
+impl Problem<Permutation> for PermutationProblem<'_> {
+  type Incremental = NotIncremental;
+
+  fn initial_solution(&self) -> Permutation {
+    let mut by_target: Permutation = self.problem.scheduled_planes().map(|p| p.id).collect();
+    by_target.sort_by_key(|&id| {
+      let window = self.problem.landing_window(id);
+      let target = self.problem.planes[id].target_landing;
+      target.clamp(*window.start(), *window.end())
+    });
+    if self.problem.timing(&by_target).is_ok() {
+      return by_target;
+    }
+    // Landing by deadline is the order most likely to be feasible
+    let mut by_latest = by_target;
+    by_latest.sort_by_key(|&id| *self.problem.landing_window(id).end());
+    by_latest
+  }
+
+  fn random_neighbor<R: Rng + ?Sized>(
+    &self,
+    permutation: &Permutation,
+    rng: &mut R,
+  ) -> Permutation {
+    let neighborhood = *self.neighborhoods.choose(rng).unwrap();
+    self.random_neighbor_in(neighborhood, permutation, rng)
+  }
+
+  fn first_improvement_neighbor<R: Rng + ?Sized>(
+    &self,
+    permutation: &Permutation,
+    rng: &mut R,
+  ) -> Permutation {
+    self
+      .neighborhoods
+      .iter()
+      .find_map(|&neighborhood| self.first_improvement_in(neighborhood, permutation, rng))
+      .unwrap_or_else(|| permutation.clone())
+  }
+
+  fn cost(&self, permutation: &Permutation) -> f64 {
+    match self.problem.timing(permutation) {
+      Ok(solution) => self.problem.landing_cost(&solution),
+      Err(_) => self.problem.penalized_cost(permutation),
+    }
+  }
+}
+
+impl Neighborhoods<Permutation> for PermutationProblem<'_> {
+  type Neighborhood = Neighborhood;
+
+  fn neighborhoods(&self) -> Vec<Neighborhood> {
+    self.neighborhoods.clone()
+  }
+
+  fn random_neighbor_in<R: Rng + ?Sized>(
+    &self,
+    neighborhood: Neighborhood,
+    permutation: &Permutation,
+    rng: &mut R,
+  ) -> Permutation {
+    let mut new_permutation = permutation.clone();
+    if permutation.len() > 1 {
+      neighborhood
+        .random_move(permutation.len(), rng)
+        .apply(&mut new_permutation);
+    }
+    new_permutation
+  }
+
+  /// First move of `neighborhood` that lowers the cost, visited in random order
+  fn first_improvement_in<R: Rng + ?Sized>(
+    &self,
+    neighborhood: Neighborhood,
+    permutation: &Permutation,
+    rng: &mut R,
+  ) -> Option<Permutation> {
+    let cost = self.cost(permutation);
+    let mut moves = neighborhood.moves(permutation.len());
+    moves.shuffle(rng);
+    moves.into_iter().find_map(|m| {
+      let mut new_permutation = permutation.clone();
+      m.apply(&mut new_permutation);
+      (self.cost(&new_permutation) < cost).then_some(new_permutation)
+    })
+  }
+}
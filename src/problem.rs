@@ -1,17 +1,20 @@
-use crate::metaheuristics::{self, initial_temperature, Problem};
+use crate::metaheuristics::{self, initial_temperature, IncrementalProblem, Problem, TabuProblem};
 use crate::parser;
 use core::fmt;
 use rand::prelude::Distribution;
 use rand::{seq::SliceRandom, Rng};
 use std::iter::zip;
-use std::ops::IndexMut;
+use std::ops::{IndexMut, RangeInclusive};
 use std::{collections::HashSet, env::args, fmt::Debug, time};
 
-type ID = usize;
+pub type ID = usize;
+pub type Runway = usize;
 
 #[derive(Debug)]
 pub struct Plane {
   pub id: ID,
+  /// When the plane shows up to the scheduler
+  pub appearance_time: u32,
   pub earliest_landing: u32,
   pub target_landing: u32,
   pub latest_landing: u32,
@@ -46,13 +49,19 @@ impl fmt::Display for Plane {
 pub struct Arrival {
   pub plane_id: ID,
   pub landing_time: u32,
+  pub runway: Runway,
 }
 
 impl Arrival {
   pub fn new(plane_id: ID, landing_time: u32) -> Self {
+    Self::on_runway(plane_id, landing_time, 0)
+  }
+
+  pub fn on_runway(plane_id: ID, landing_time: u32, runway: Runway) -> Self {
     Arrival {
       plane_id,
       landing_time,
+      runway,
     }
   }
 }
@@ -60,60 +69,291 @@ impl Arrival {
 /// Ordered list of arrivals
 pub type Solution = Vec<Arrival>;
 
+/// Lands the arrival at `index` at another time or on another runway
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrivalMove {
+  pub index: usize,
+  pub landing_time: u32,
+  pub runway: Runway,
+}
+
+/// Width of the time ranges a tabu search keeps planes out of
+const TABU_TIME_BUCKET: u32 = 10;
+
 /// Cost of a conflict of landing times between two planes per unit of time
-const CONFLICT_PENALTY: f64 = 5000.0;
+pub(crate) const CONFLICT_PENALTY: f64 = 5000.0;
 
 #[derive(Debug)]
 pub struct LandingProblem {
   pub planes: Vec<Plane>,
-  pub uniform: rand::distributions::Uniform<usize>,
+  pub runways: usize,
+  /// Separation times between planes landing on different runways, indexed
+  /// like `Plane::separation_times`. Without it, runways are independent
+  pub cross_runway_separation: Option<Vec<Vec<u32>>>,
+  /// Largest separation time between two different planes, bounds how far
+  /// ahead `conflicts` has to look
+  pub(crate) max_separation: u32,
+  /// How long before landing a schedule can no longer change
+  pub freeze_time: u32,
+  /// When set, only planes that have appeared are scheduled, and the landings
+  /// within the freeze time are fixed
+  pub horizon: Option<Horizon>,
+}
+
+/// State of a schedule being revised at a given time
+#[derive(Debug, Clone)]
+pub struct Horizon {
+  pub now: u32,
+  /// Arrivals that cannot change anymore, indexed by plane id
+  pub frozen: Vec<Option<Arrival>>,
+}
+
+fn max_separation<'a>(separation_times: impl IntoIterator<Item = &'a Vec<u32>>) -> u32 {
+  separation_times
+    .into_iter()
+    .enumerate()
+    .flat_map(|(a, row)| {
+      row
+        .iter()
+        .enumerate()
+        .filter(move |&(b, _)| a != b)
+        .map(|(_, &s)| s)
+    })
+    .max()
+    .unwrap_or(0)
 }
 
 impl LandingProblem {
   pub fn from_parser(data: parser::ProblemData) -> Self {
     assert_eq!(data.num_planes, data.planes.len());
+    let planes: Vec<_> = data
+      .planes
+      .into_iter()
+      .enumerate()
+      .map(|(id, p)| Plane {
+        id,
+        appearance_time: p.appearance_time,
+        earliest_landing: p.earliest_landing,
+        target_landing: p.target_landing,
+        latest_landing: p.latest_landing,
+        penalty_before: p.penalty_before,
+        penalty_after: p.penalty_after,
+        separation_times: p.separation_times,
+      })
+      .collect();
     LandingProblem {
-      planes: data
-        .planes
-        .into_iter()
-        .enumerate()
-        .map(|(id, p)| Plane {
-          id,
-          earliest_landing: p.earliest_landing,
-          target_landing: p.target_landing,
-          latest_landing: p.latest_landing,
-          penalty_before: p.penalty_before,
-          penalty_after: p.penalty_after,
-          separation_times: p.separation_times,
-        })
-        .collect(),
-      uniform: rand::distributions::Uniform::new(0, data.num_planes),
+      max_separation: max_separation(planes.iter().map(|p| &p.separation_times)),
+      planes,
+      runways: 1,
+      cross_runway_separation: None,
+      freeze_time: data.freeze_time,
+      horizon: None,
     }
   }
 
+  pub fn with_runways(mut self, runways: usize) -> Self {
+    assert!(runways > 0, "at least one runway is needed");
+    self.runways = runways;
+    self
+  }
+
+  pub fn with_cross_runway_separation(mut self, separation_times: Vec<Vec<u32>>) -> Self {
+    assert_eq!(separation_times.len(), self.planes.len());
+    self.max_separation = self.max_separation.max(max_separation(&separation_times));
+    self.cross_runway_separation = Some(separation_times);
+    self
+  }
+
+  /// Revises `schedule` at time `now`: planes that have not appeared are left
+  /// out, arrivals within the freeze time are kept as they are, and the other
+  /// planes cannot land before the freeze time is over
+  pub fn with_horizon(mut self, now: u32, schedule: &Solution) -> Self {
+    self.set_horizon(now, schedule);
+    self
+  }
+
+  pub fn set_horizon(&mut self, now: u32, schedule: &Solution) {
+    let mut frozen = vec![None; self.planes.len()];
+    for arrival in schedule {
+      if arrival.landing_time <= now + self.freeze_time {
+        frozen[arrival.plane_id] = Some(*arrival);
+      }
+    }
+    self.horizon = Some(Horizon { now, frozen });
+  }
+
+  /// Planes to schedule, those that have appeared when there is a horizon
+  pub fn scheduled_planes(&self) -> impl Iterator<Item = &Plane> {
+    self.planes.iter().filter(|plane| match &self.horizon {
+      Some(horizon) => plane.appearance_time <= horizon.now,
+      None => true,
+    })
+  }
+
+  /// Arrival of `plane_id` that the horizon does not allow to change
+  pub fn frozen(&self, plane_id: ID) -> Option<&Arrival> {
+    self.horizon.as_ref()?.frozen[plane_id].as_ref()
+  }
+
+  /// Times at which `plane_id` can land
+  pub fn landing_window(&self, plane_id: ID) -> RangeInclusive<u32> {
+    let plane = &self.planes[plane_id];
+    let Some(horizon) = &self.horizon else {
+      return plane.earliest_landing..=plane.latest_landing;
+    };
+    if let Some(arrival) = &horizon.frozen[plane_id] {
+      return arrival.landing_time..=arrival.landing_time;
+    }
+    let earliest = plane
+      .earliest_landing
+      .max(plane.appearance_time)
+      .max(horizon.now + self.freeze_time + 1)
+      .min(plane.latest_landing);
+    earliest..=plane.latest_landing
+  }
+
+  /// Checks every ordered pair of arrivals, not only neighbours, as separation
+  /// times do not satisfy the triangle inequality. Later arrivals are only
+  /// scanned while they are within the largest separation time, so
+  /// `solution` must be sorted by landing time
   pub fn conflicts<'a>(
     &'a self,
     solution: &'a Solution,
   ) -> impl Iterator<Item = (Arrival, Arrival, u32)> + 'a {
-    solution.array_windows::<2>().filter_map(|[a, b]| {
-      let max_landing_time = b.landing_time - self.separation_time_between(a.plane_id, b.plane_id);
+    debug_assert!(solution.is_sorted_by_key(|a| a.landing_time));
+    solution.iter().enumerate().flat_map(move |(i, a)| {
+      solution[i + 1..]
+        .iter()
+        .take_while(move |b| b.landing_time < a.landing_time + self.max_separation)
+        .filter_map(move |b| match self.conflict_between(a, b) {
+          0 => None,
+          duration => Some((*a, *b, duration)),
+        })
+    })
+  }
 
-      if a.landing_time > max_landing_time {
-        Some((*a, *b, a.landing_time - max_landing_time))
-      } else {
-        None
+  /// Separation time missing between `a` and `b` landing after it
+  pub(crate) fn conflict_between(&self, a: &Arrival, b: &Arrival) -> u32 {
+    if a.runway != b.runway && self.cross_runway_separation.is_none() {
+      return 0;
+    }
+    (a.landing_time + self.separation_between(a, b)).saturating_sub(b.landing_time)
+  }
+
+  /// Conflict duration between `arrival`, landing at `position` in
+  /// `solution`, and the arrivals within separation range of it, leaving out
+  /// the one at `skip`
+  fn conflicts_around(
+    &self,
+    solution: &Solution,
+    skip: usize,
+    arrival: &Arrival,
+    position: usize,
+  ) -> u32 {
+    let before = solution[..position]
+      .iter()
+      .enumerate()
+      .rev()
+      .filter(|&(i, _)| i != skip)
+      .map(|(_, a)| a)
+      .take_while(|a| a.landing_time + self.max_separation > arrival.landing_time)
+      .map(|a| self.conflict_between(a, arrival));
+    let after = solution[position..]
+      .iter()
+      .enumerate()
+      .filter(|&(i, _)| position + i != skip)
+      .map(|(_, b)| b)
+      .take_while(|b| b.landing_time < arrival.landing_time + self.max_separation)
+      .map(|b| self.conflict_between(arrival, b));
+    before.chain(after).sum()
+  }
+
+  /// Cost change of applying `m`, only looking at the moved arrival and the
+  /// ones within separation range of it
+  pub fn move_delta(&self, solution: &Solution, m: &ArrivalMove) -> f64 {
+    let arrival = solution[m.index];
+    let moved = Arrival::on_runway(arrival.plane_id, m.landing_time, m.runway);
+    let plane = &self.planes[arrival.plane_id];
+
+    let old_conflicts = self.conflicts_around(solution, m.index, &arrival, m.index);
+    let position = solution.partition_point(|a| a.landing_time <= moved.landing_time);
+    let new_conflicts = self.conflicts_around(solution, m.index, &moved, position);
+
+    plane.cost_for_landing(moved.landing_time) - plane.cost_for_landing(arrival.landing_time)
+      + CONFLICT_PENALTY * (new_conflicts as f64 - old_conflicts as f64)
+  }
+
+  /// Random move of `random_neighbor`: an arrival landing at another time
+  /// of its window, or on another runway. `None` without arrivals, as
+  /// happens before any plane appears
+  fn random_arrival_move<R: Rng + ?Sized>(
+    &self,
+    solution: &Solution,
+    rng: &mut R,
+  ) -> Option<ArrivalMove> {
+    if solution.is_empty() {
+      return None;
+    }
+    // Frozen arrivals cannot move, when all of them are the move does nothing
+    let index = (0..solution.len())
+      .map(|_| rng.gen_range(0..solution.len()))
+      .find(|&i| self.frozen(solution[i].plane_id).is_none())
+      .unwrap_or(0);
+    let arrival = solution[index];
+
+    let m = if self.frozen(arrival.plane_id).is_some() {
+      ArrivalMove {
+        index,
+        landing_time: arrival.landing_time,
+        runway: arrival.runway,
       }
-    })
+    } else if self.runways > 1 && rng.gen_bool(0.5) {
+      // Same landing time on any other runway
+      let runway = rng.gen_range(0..self.runways - 1);
+      ArrivalMove {
+        index,
+        landing_time: arrival.landing_time,
+        runway: if runway >= arrival.runway {
+          runway + 1
+        } else {
+          runway
+        },
+      }
+    } else {
+      ArrivalMove {
+        index,
+        landing_time: rng.gen_range(self.landing_window(arrival.plane_id)),
+        runway: arrival.runway,
+      }
+    };
+    Some(m)
   }
 
   pub fn is_valid(&self, solution: &Solution) -> bool {
-    self.conflicts(solution).next().is_none()
+    solution
+      .iter()
+      .all(|a| self.landing_window(a.plane_id).contains(&a.landing_time))
+      && self.conflicts(solution).next().is_none()
   }
 
   pub fn separation_time_between(&self, a: ID, b: ID) -> u32 {
     self.planes[a].separation_times[b]
   }
 
+  /// Separation needed when `b` lands after `a`, taking their runways into account
+  pub fn separation_between(&self, a: &Arrival, b: &Arrival) -> u32 {
+    if a.runway == b.runway {
+      self.separation_time_between(a.plane_id, b.plane_id)
+    } else {
+      self
+        .cross_runway_separation
+        .as_ref()
+        .map_or(0, |separation_times| {
+          separation_times[a.plane_id][b.plane_id]
+        })
+    }
+  }
+
   pub fn landing_cost(&self, solution: &Solution) -> f64 {
     solution
       .iter()
@@ -125,65 +365,284 @@ impl LandingProblem {
     self
       .conflicts(solution)
       .map(|(_, _, conflict_duration)| CONFLICT_PENALTY * conflict_duration as f64)
-      .sum()
+      // An empty sum of floats is -0
+      .fold(0.0, |sum, cost| sum + cost)
   }
 }
 
 impl metaheuristics::Problem<Solution> for LandingProblem {
+  type Incremental = Self;
+
   fn initial_solution(&self) -> Solution {
     let mut s: Vec<_> = self
-      .planes
-      .iter()
-      .map(|p| Arrival::new(p.id, p.target_landing))
+      .scheduled_planes()
+      .map(|p| {
+        let window = self.landing_window(p.id);
+        Arrival::new(p.id, p.target_landing.clamp(*window.start(), *window.end()))
+      })
       .collect();
-    s.sort_by(|a, b| a.landing_time.cmp(&b.landing_time));
+    s.sort_by_key(|a| a.landing_time);
+    for (i, arrival) in s.iter_mut().enumerate() {
+      arrival.runway = match self.frozen(arrival.plane_id) {
+        Some(frozen) => frozen.runway,
+        None => i % self.runways,
+      };
+    }
     s
   }
 
-  fn random_neighbor(&self, solution: &Solution) -> Solution {
-    let mut rng = rand::thread_rng();
-    let arrival_i = self.uniform.sample(&mut rng);
+  fn random_neighbor<R: Rng + ?Sized>(&self, solution: &Solution, rng: &mut R) -> Solution {
+    // Without the cost delta of `random_move`, as the neighbor is evaluated
+    let mut new_solution = solution.clone();
+    if let Some(m) = self.random_arrival_move(solution, rng) {
+      self.apply_move(&mut new_solution, m);
+    }
+    new_solution
+  }
 
+  fn first_improvement_neighbor<R: Rng + ?Sized>(
+    &self,
+    solution: &Solution,
+    rng: &mut R,
+  ) -> Solution {
     let mut new_solution = solution.clone();
-    let arrival = new_solution.index_mut(arrival_i);
-    let plane = &self.planes[arrival.plane_id];
-    let landing_time = rng.gen_range(plane.earliest_landing..=plane.latest_landing);
-    arrival.landing_time = landing_time;
-    new_solution.sort_by_key(|a| a.landing_time);
+    if let Some((m, _)) = self.first_improvement_move(solution, rng) {
+      self.apply_move(&mut new_solution, m);
+    }
     new_solution
   }
 
-  fn first_improvement_neighbor(&self, solution: &Solution) -> Solution {
-    let mut rng = rand::thread_rng();
+  fn cost(&self, solution: &Solution) -> f64 {
+    self.landing_cost(solution) + self.conflict_cost(solution)
+  }
+
+  fn incremental(&self) -> Option<&Self> {
+    Some(self)
+  }
+}
+
+impl IncrementalProblem<Solution> for LandingProblem {
+  type Move = ArrivalMove;
+
+  fn random_move<R: Rng + ?Sized>(
+    &self,
+    solution: &Solution,
+    rng: &mut R,
+  ) -> Option<(ArrivalMove, f64)> {
+    let m = self.random_arrival_move(solution, rng)?;
+    Some((m, self.move_delta(solution, &m)))
+  }
+
+  fn first_improvement_move<R: Rng + ?Sized>(
+    &self,
+    solution: &Solution,
+    rng: &mut R,
+  ) -> Option<(ArrivalMove, f64)> {
     let mut arrival_is = (0..solution.len()).collect::<Vec<_>>();
-    arrival_is.shuffle(&mut rng);
+    arrival_is.shuffle(rng);
 
-    for arrival_i in arrival_is {
-      let arrival = solution[arrival_i];
-      let plane = &self.planes[arrival.plane_id];
+    for index in arrival_is {
+      let arrival = solution[index];
+      if self.frozen(arrival.plane_id).is_some() {
+        continue;
+      }
+      let window = self.landing_window(arrival.plane_id);
+
+      let other_runways = (0..self.runways)
+        .filter(|&r| r != arrival.runway)
+        .map(|runway| ArrivalMove {
+          index,
+          landing_time: arrival.landing_time,
+          runway,
+        });
 
       //  [0  1  2 ...  30].reverse()
-      let towards_earliest = (plane.earliest_landing..=arrival.landing_time).rev();
+      let towards_earliest = (*window.start()..=arrival.landing_time).rev();
       // [31 32 33 ... 100]
-      let towards_latest = (arrival.landing_time..=plane.latest_landing);
+      let towards_latest = (arrival.landing_time..=*window.end());
 
       // [30 31 29 32 28 33 ... 0 100]
-      let zigzag_times = zip(towards_earliest, towards_latest).flat_map(|(e, l)| [e, l]);
-
-      for time in zigzag_times {
-        let mut new_solution = solution.clone();
-        new_solution[arrival_i].landing_time = time;
-        new_solution.sort_by_key(|a| a.landing_time);
+      let zigzag_times = zip(towards_earliest, towards_latest)
+        .flat_map(|(e, l)| [e, l])
+        .map(|landing_time| ArrivalMove {
+          index,
+          landing_time,
+          runway: arrival.runway,
+        });
 
-        if self.cost(&new_solution) < self.cost(solution) {
-          return new_solution;
+      for m in other_runways.chain(zigzag_times) {
+        let delta = self.move_delta(solution, &m);
+        if delta < 0.0 {
+          return Some((m, delta));
         }
       }
     }
-    solution.clone()
+    None
   }
 
-  fn cost(&self, solution: &Solution) -> f64 {
-    self.landing_cost(solution) + self.conflict_cost(solution)
+  fn apply_move(&self, solution: &mut Solution, m: ArrivalMove) {
+    let mut arrival = solution.remove(m.index);
+    arrival.landing_time = m.landing_time;
+    arrival.runway = m.runway;
+    let index = solution.partition_point(|a| a.landing_time <= m.landing_time);
+    solution.insert(index, arrival);
+  }
+}
+
+/// A plane landing on a runway within a range of `TABU_TIME_BUCKET` units
+impl TabuProblem<Solution> for LandingProblem {
+  type Attribute = (ID, Runway, u32);
+
+  fn dropped_attribute(&self, solution: &Solution, m: &ArrivalMove) -> Self::Attribute {
+    let arrival = solution[m.index];
+    (
+      arrival.plane_id,
+      arrival.runway,
+      arrival.landing_time / TABU_TIME_BUCKET,
+    )
+  }
+
+  fn added_attribute(&self, solution: &Solution, m: &ArrivalMove) -> Self::Attribute {
+    (
+      solution[m.index].plane_id,
+      m.runway,
+      m.landing_time / TABU_TIME_BUCKET,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parser::ProblemData;
+  use rand::rngs::StdRng;
+  use rand::SeedableRng;
+
+  fn separation<R: Rng>(n: usize, max: u32, rng: &mut R) -> Vec<Vec<u32>> {
+    (0..n)
+      .map(|a| {
+        (0..n)
+          .map(|b| {
+            if a == b {
+              99999
+            } else {
+              rng.gen_range(0..=max)
+            }
+          })
+          .collect()
+      })
+      .collect()
+  }
+
+  /// Planes with wide windows over a short span, so that landings tie and
+  /// fall within separation range of each other, on up to 3 runways with or
+  /// without separation between runways
+  fn random_problem<R: Rng>(rng: &mut R) -> LandingProblem {
+    let n = rng.gen_range(2..=6);
+    let separation_times = separation(n, 8, rng);
+    let planes = separation_times
+      .into_iter()
+      .map(|separation_times| parser::Plane {
+        appearance_time: 0,
+        earliest_landing: 0,
+        target_landing: rng.gen_range(0..=20),
+        latest_landing: 30,
+        penalty_before: rng.gen_range(1..=9) as f64,
+        penalty_after: rng.gen_range(1..=9) as f64,
+        separation_times,
+      })
+      .collect();
+    let problem = LandingProblem::from_parser(ProblemData {
+      num_planes: n,
+      freeze_time: 0,
+      planes,
+      replaced: Vec::new(),
+    })
+    .with_runways(rng.gen_range(1..=3));
+    if rng.gen_bool(0.5) {
+      let cross_runway_separation = separation(n, 4, rng);
+      problem.with_cross_runway_separation(cross_runway_separation)
+    } else {
+      problem
+    }
+  }
+
+  #[test]
+  fn move_delta_matches_cost_change() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..2000 {
+      let problem = random_problem(&mut rng);
+      let mut solution: Solution = (0..problem.planes.len())
+        .map(|id| {
+          // Few distinct times, for ties
+          let landing_time = rng.gen_range(0..=6) * 5;
+          Arrival::on_runway(id, landing_time, rng.gen_range(0..problem.runways))
+        })
+        .collect();
+      solution.sort_by_key(|a| a.landing_time);
+
+      let index = rng.gen_range(0..solution.len());
+      let other = solution[rng.gen_range(0..solution.len())].landing_time;
+      let max = problem.max_separation;
+      let landing_time = match rng.gen_range(0..3) {
+        0 => rng.gen_range(0..=30),
+        // Tied with another arrival
+        1 => other,
+        // Just within or just out of separation range of another arrival
+        _ => (other + max + rng.gen_range(0..=2))
+          .saturating_sub(1)
+          .min(30),
+      };
+      let m = ArrivalMove {
+        index,
+        landing_time,
+        runway: rng.gen_range(0..problem.runways),
+      };
+
+      let delta = problem.move_delta(&solution, &m);
+      let before = problem.cost(&solution);
+      let mut after = solution.clone();
+      problem.apply_move(&mut after, m);
+      assert!(after.is_sorted_by_key(|a| a.landing_time));
+      let expected = problem.cost(&after) - before;
+      assert!(
+        (delta - expected).abs() < 1e-6,
+        "{:?} on {:?}: delta {} instead of {}",
+        m,
+        solution,
+        delta,
+        expected
+      );
+    }
+  }
+
+  #[test]
+  fn cross_runway_separation_applies_between_runways() {
+    let text = "2 0\n0 0 10 50 1 1\n99999 6\n0 0 10 50 1 1\n6 99999\n";
+    let data = parser::parse_problem(text.as_bytes(), parser::ParseMode::Strict).unwrap();
+    let cross =
+      parser::parse_separation_matrix("0 3\n2 0\n".as_bytes(), 2, parser::ParseMode::Strict);
+    let problem = LandingProblem::from_parser(data).with_runways(2);
+    let a = Arrival::on_runway(0, 10, 0);
+    let b = Arrival::on_runway(1, 11, 1);
+    let solution = vec![a, b];
+    // Independent runways without the matrix
+    assert_eq!(problem.separation_between(&a, &b), 0);
+    assert_eq!(problem.conflicts(&solution).count(), 0);
+
+    let problem = problem.with_cross_runway_separation(cross.unwrap().separation_times);
+    assert_eq!(problem.separation_between(&a, &b), 3);
+    assert_eq!(problem.separation_between(&b, &a), 2);
+    assert_eq!(
+      problem.conflicts(&solution).collect::<Vec<_>>(),
+      vec![(a, b, 2)]
+    );
+    // The same runway still uses the separation times of the instance
+    let c = Arrival::on_runway(1, 11, 0);
+    assert_eq!(problem.separation_between(&a, &c), 6);
+    assert_eq!(
+      problem.conflicts(&vec![a, c]).collect::<Vec<_>>(),
+      vec![(a, c, 5)]
+    );
   }
 }
@@ -0,0 +1,615 @@
+use crate::parallel::run_parallel;
+use crate::statistics::{friedman, friedman_post_hoc, Friedman};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::{error, fmt};
+
+/// Significant digits kept on sampled real values, as irace does by default
+const DIGITS: i32 = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Domain {
+  Real {
+    lower: f64,
+    upper: f64,
+    log: bool,
+  },
+  Integer {
+    lower: i64,
+    upper: i64,
+    log: bool,
+  },
+  Categorical(Vec<String>),
+  /// Levels in increasing order, sampled by position
+  Ordinal(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+  pub name: String,
+  /// Put right before the value on the command line
+  pub switch: String,
+  pub domain: Domain,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Real(f64),
+  Integer(i64),
+  Label(String),
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Value::Real(value) => write!(f, "{}", value),
+      Value::Integer(value) => write!(f, "{}", value),
+      Value::Label(label) => write!(f, "{}", label),
+    }
+  }
+}
+
+/// Parameters of a target algorithm, in the format of irace's
+/// `parameters.txt`: `name "switch" r|i|c|o[,log] (domain)` on every line.
+/// Conditions and forbidden configurations are not supported
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterSpace {
+  pub parameters: Vec<Parameter>,
+}
+
+impl ParameterSpace {
+  pub fn read(reader: impl BufRead) -> Result<Self, ParameterError> {
+    let mut parameters = Vec::new();
+    for (k, line) in reader.lines().enumerate() {
+      let line = line?;
+      let row = line.split('#').next().unwrap().trim();
+      if row.is_empty() {
+        continue;
+      }
+      let parameter = parse_parameter(row).ok_or_else(|| ParameterError::Format {
+        line: k + 1,
+        row: line.clone(),
+      })?;
+      parameters.push(parameter);
+    }
+    Ok(ParameterSpace { parameters })
+  }
+
+  pub fn read_from(file_path: &str) -> Result<Self, ParameterError> {
+    Self::read(io::BufReader::new(File::open(file_path)?))
+  }
+
+  /// Arguments of the target algorithm for `values`: every switch followed by
+  /// its value, as irace passes them
+  pub fn command_line(&self, values: &[Value]) -> String {
+    self
+      .parameters
+      .iter()
+      .zip(values)
+      .map(|(parameter, value)| format!("{}{}", parameter.switch, value))
+      .collect::<Vec<_>>()
+      .join(" ")
+  }
+
+  fn sample_uniform<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<Value> {
+    self
+      .parameters
+      .iter()
+      .map(|parameter| match &parameter.domain {
+        Domain::Real { lower, upper, log } => {
+          let value = rng.gen_range(scale(*lower, *log)..=scale(*upper, *log));
+          Value::Real(unscale(value, *log))
+        }
+        Domain::Integer { lower, upper, log } => {
+          let range = scale(*lower as f64, *log)..scale(*upper as f64 + 1.0, *log);
+          let value = unscale(rng.gen_range(range), *log).floor() as i64;
+          Value::Integer(value.min(*upper))
+        }
+        Domain::Categorical(levels) | Domain::Ordinal(levels) => {
+          Value::Label(levels.choose(rng).unwrap().clone())
+        }
+      })
+      .collect()
+  }
+
+  /// A configuration near `parent`: numbers follow a normal distribution
+  /// around the parent's, with `spread` times the range as deviation, and
+  /// categories are redrawn with probability `2 * spread`
+  fn sample_around<R: Rng + ?Sized>(
+    &self,
+    parent: &[Value],
+    spread: f64,
+    rng: &mut R,
+  ) -> Vec<Value> {
+    let near = |rng: &mut R, value: f64, lower: f64, upper: f64| {
+      let sample = value + normal(rng) * spread * (upper - lower);
+      sample.clamp(lower, upper)
+    };
+    self
+      .parameters
+      .iter()
+      .zip(parent)
+      .map(|(parameter, value)| match (&parameter.domain, value) {
+        (Domain::Real { lower, upper, log }, Value::Real(value)) => {
+          let value = near(
+            rng,
+            scale(*value, *log),
+            scale(*lower, *log),
+            scale(*upper, *log),
+          );
+          Value::Real(unscale(value, *log))
+        }
+        (Domain::Integer { lower, upper, log }, Value::Integer(value)) => {
+          let value = near(
+            rng,
+            scale(*value as f64, *log),
+            scale(*lower as f64, *log),
+            scale(*upper as f64, *log),
+          );
+          Value::Integer((unscale(value, *log).round() as i64).clamp(*lower, *upper))
+        }
+        (Domain::Ordinal(levels), Value::Label(label)) => {
+          let position = levels.iter().position(|level| level == label).unwrap();
+          let position = near(rng, position as f64, 0.0, (levels.len() - 1) as f64);
+          Value::Label(levels[position.round() as usize].clone())
+        }
+        (Domain::Categorical(levels), Value::Label(label)) => {
+          if rng.gen_bool((2.0 * spread).min(1.0)) {
+            Value::Label(levels.choose(rng).unwrap().clone())
+          } else {
+            Value::Label(label.clone())
+          }
+        }
+        _ => panic!("{} does not match the domain of {}", value, parameter.name),
+      })
+      .collect()
+  }
+
+  /// Writes `elites` as an irace configurations file: a header of parameter
+  /// names, then the values of every configuration
+  pub fn write_configurations(&self, elites: &[Elite], writer: &mut impl Write) -> io::Result<()> {
+    let names: Vec<&str> = self.parameters.iter().map(|p| p.name.as_str()).collect();
+    writeln!(writer, "{}", names.join(" "))?;
+    for elite in elites {
+      let values: Vec<String> = elite.values.iter().map(Value::to_string).collect();
+      writeln!(writer, "{}", values.join(" "))?;
+    }
+    Ok(())
+  }
+}
+
+fn parse_parameter(row: &str) -> Option<Parameter> {
+  let (name, rest) = row.split_once(char::is_whitespace)?;
+  let rest = rest.trim_start().strip_prefix('"')?;
+  let (switch, rest) = rest.split_once('"')?;
+  let (kind, rest) = rest.trim_start().split_once(char::is_whitespace)?;
+  let (levels, rest) = rest.trim_start().strip_prefix('(')?.split_once(')')?;
+  if !rest.trim().is_empty() {
+    return None;
+  }
+  let levels: Vec<&str> = levels
+    .split(',')
+    .map(|level| level.trim().trim_matches('"'))
+    .collect();
+  let (kind, log) = match kind.split_once(',') {
+    Some((kind, "log")) => (kind, true),
+    Some(_) => return None,
+    None => (kind, false),
+  };
+  let domain = match (kind, &levels[..]) {
+    ("r", [lower, upper]) => Domain::Real {
+      lower: lower.parse().ok()?,
+      upper: upper.parse().ok()?,
+      log,
+    },
+    ("i", [lower, upper]) => Domain::Integer {
+      lower: lower.parse().ok()?,
+      upper: upper.parse().ok()?,
+      log,
+    },
+    ("c", _) if !log => Domain::Categorical(levels.iter().map(|s| s.to_string()).collect()),
+    ("o", _) if !log => Domain::Ordinal(levels.iter().map(|s| s.to_string()).collect()),
+    _ => return None,
+  };
+  let valid = match domain {
+    Domain::Real { lower, upper, log } => lower <= upper && (!log || lower > 0.0),
+    Domain::Integer { lower, upper, log } => lower <= upper && (!log || lower > 0),
+    Domain::Categorical(_) | Domain::Ordinal(_) => levels.iter().all(|level| !level.is_empty()),
+  };
+  valid.then(|| Parameter {
+    name: name.to_string(),
+    switch: switch.to_string(),
+    domain,
+  })
+}
+
+/// `value` on the scale it is sampled on
+fn scale(value: f64, log: bool) -> f64 {
+  if log {
+    value.ln()
+  } else {
+    value
+  }
+}
+
+fn unscale(value: f64, log: bool) -> f64 {
+  if log {
+    value.exp()
+  } else {
+    value
+  }
+}
+
+/// `value` rounded to `DIGITS` significant digits
+fn rounded(value: Value) -> Value {
+  match value {
+    Value::Real(value) if value != 0.0 => {
+      let scale = 10f64.powi(DIGITS - 1 - value.abs().log10().floor() as i32);
+      Value::Real((value * scale).round() / scale)
+    }
+    value => value,
+  }
+}
+
+/// Standard normal sample (Box-Muller)
+fn normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+  let u: f64 = 1.0 - rng.gen::<f64>();
+  let v: f64 = rng.gen();
+  (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaceSettings {
+  /// Total number of runs of the target algorithm
+  pub budget: usize,
+  /// Instances every configuration runs on before the first elimination
+  pub first_test: usize,
+  /// Instances between eliminations
+  pub each_test: usize,
+  /// Level of the Friedman test and of its post-hoc comparisons
+  pub significance: f64,
+}
+
+impl Default for RaceSettings {
+  fn default() -> Self {
+    RaceSettings {
+      budget: 1000,
+      first_test: 5,
+      each_test: 1,
+      significance: 0.05,
+    }
+  }
+}
+
+/// A configuration surviving the races
+#[derive(Debug, Clone, PartialEq)]
+pub struct Elite {
+  pub values: Vec<Value>,
+  /// Mean rank in the last race it ran in, 1 being the best
+  pub mean_rank: f64,
+  pub mean_cost: f64,
+  /// Number of instances it ran on
+  pub experiments: usize,
+}
+
+struct Candidate {
+  values: Vec<Value>,
+  /// Costs on the first experiments
+  costs: Vec<f64>,
+}
+
+/// Iterated F-race (López-Ibáñez et al., 2016) over `instances` instances.
+/// Every iteration races configurations sampled around the elites of the
+/// previous one on a stream of (instance, seed) experiments shared by all
+/// iterations, and after `first_test` experiments drops after each
+/// `each_test` the configurations that the Friedman test and its post-hoc
+/// comparisons find worse than the best. `evaluate(values, instance, seed)`
+/// is the cost of a run, to minimize. Runs within an experiment are spread on
+/// `workers` threads, with their seeds coming from `rng` only, so the result
+/// is the same whatever the number of workers if `evaluate` is deterministic.
+/// Returns the elites, best first, none without instances
+pub fn iterated_race<R, E>(
+  space: &ParameterSpace,
+  instances: usize,
+  settings: RaceSettings,
+  workers: usize,
+  rng: &mut R,
+  evaluate: E,
+) -> Vec<Elite>
+where
+  R: Rng + ?Sized,
+  E: Fn(&[Value], usize, u64) -> f64 + Sync,
+{
+  if instances == 0 {
+    return Vec::new();
+  }
+  let dimensions = space.parameters.len().max(1) as f64;
+  let iterations = 2 + dimensions.log2().floor() as usize;
+  let survivors = 2 + dimensions.log2().floor() as usize;
+  let mut experiments: Vec<(usize, u64)> = Vec::new();
+  let mut elites: Vec<Candidate> = Vec::new();
+  let mut ranks: Vec<f64> = Vec::new();
+  let mut used = 0;
+
+  for iteration in 1..=iterations {
+    let budget = (settings.budget - used) / (iterations - iteration + 1);
+    let size = budget / (settings.first_test + iteration.min(5));
+    if size <= elites.len() {
+      break;
+    }
+    let spread =
+      0.5 * (1.0 / (size - elites.len()) as f64).powf((iteration - 1) as f64 / dimensions);
+    let mut candidates = std::mem::take(&mut elites);
+    // Parents are drawn with weights decreasing linearly with their rank
+    let parents: Vec<usize> = (0..candidates.len()).collect();
+    while candidates.len() < size {
+      let values = match parents.choose_weighted(rng, |&r| parents.len() - r) {
+        Ok(&parent) => space.sample_around(&candidates[parent].values, spread, rng),
+        Err(_) => space.sample_uniform(rng),
+      };
+      candidates.push(Candidate {
+        values: values.into_iter().map(rounded).collect(),
+        costs: Vec::new(),
+      });
+    }
+
+    let mut alive: Vec<usize> = (0..candidates.len()).collect();
+    let mut spent = 0;
+    let mut t = 0;
+    while alive.len() > survivors || t < settings.first_test {
+      let missing: Vec<usize> = alive
+        .iter()
+        .copied()
+        .filter(|&c| candidates[c].costs.len() <= t)
+        .collect();
+      if spent + missing.len() > budget {
+        break;
+      }
+      while experiments.len() <= t {
+        // Instances in a random order, a new seed on every pass
+        let mut order: Vec<usize> = (0..instances).collect();
+        order.shuffle(rng);
+        experiments.extend(order.into_iter().map(|instance| (instance, rng.gen())));
+      }
+      let (instance, seed) = experiments[t];
+      let costs = run_parallel(missing.len(), workers, |k| {
+        evaluate(&candidates[missing[k]].values, instance, seed)
+      });
+      for (c, cost) in missing.iter().zip(costs) {
+        candidates[*c].costs.push(cost);
+      }
+      spent += missing.len();
+      t += 1;
+      if t >= settings.first_test && (t - settings.first_test).is_multiple_of(settings.each_test) {
+        alive = surviving(&candidates, &alive, t, settings.significance);
+      }
+    }
+    used += spent;
+
+    // The survivors, best first, become the elites of the next iteration
+    let test = if t > 0 {
+      Some(race_test(&candidates, &alive, t).mean_ranks)
+    } else {
+      None
+    };
+    let mut order: Vec<usize> = (0..alive.len()).collect();
+    if let Some(mean_ranks) = &test {
+      order.sort_by(|&a, &b| mean_ranks[a].partial_cmp(&mean_ranks[b]).unwrap());
+    }
+    order.truncate(survivors);
+    ranks = order
+      .iter()
+      .map(|&k| test.as_ref().map_or(0.0, |mean_ranks| mean_ranks[k]))
+      .collect();
+    let mut candidates: Vec<Option<Candidate>> = candidates.into_iter().map(Some).collect();
+    elites = order
+      .iter()
+      .map(|&k| candidates[alive[k]].take().unwrap())
+      .collect();
+    if t == 0 {
+      break;
+    }
+  }
+
+  elites
+    .into_iter()
+    .zip(ranks)
+    .map(|(elite, mean_rank)| Elite {
+      mean_cost: elite.costs.iter().sum::<f64>() / elite.costs.len().max(1) as f64,
+      experiments: elite.costs.len(),
+      values: elite.values,
+      mean_rank,
+    })
+    .collect()
+}
+
+/// Friedman test of `alive` on the first `t` experiments
+fn race_test(candidates: &[Candidate], alive: &[usize], t: usize) -> Friedman {
+  let blocks: Vec<Vec<f64>> = (0..t)
+    .map(|k| alive.iter().map(|&c| candidates[c].costs[k]).collect())
+    .collect();
+  friedman(&blocks)
+}
+
+/// `alive` without the configurations significantly worse than the best on
+/// the first `t` experiments
+fn surviving(candidates: &[Candidate], alive: &[usize], t: usize, significance: f64) -> Vec<usize> {
+  let test = race_test(candidates, alive, t);
+  if test.p_value >= significance {
+    return alive.to_vec();
+  }
+  let post_hoc = friedman_post_hoc(&test);
+  let best = (0..alive.len())
+    .min_by(|&a, &b| test.mean_ranks[a].partial_cmp(&test.mean_ranks[b]).unwrap())
+    .unwrap();
+  (0..alive.len())
+    .filter(|&k| post_hoc[best][k] >= significance)
+    .map(|k| alive[k])
+    .collect()
+}
+
+#[derive(Debug)]
+pub enum ParameterError {
+  Io(io::Error),
+  Format {
+    /// 1-based line of the offending row
+    line: usize,
+    row: String,
+  },
+}
+
+impl fmt::Display for ParameterError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ParameterError::Io(e) => write!(f, "I/O error: {}", e),
+      ParameterError::Format { line, row } => write!(
+        f,
+        "line {}: expected name \"switch\" r|i|c|o[,log] (domain) without condition, found {:?}",
+        line, row
+      ),
+    }
+  }
+}
+
+impl error::Error for ParameterError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      ParameterError::Io(e) => Some(e),
+      ParameterError::Format { .. } => None,
+    }
+  }
+}
+
+impl From<io::Error> for ParameterError {
+  fn from(e: io::Error) -> Self {
+    ParameterError::Io(e)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::rngs::StdRng;
+  use rand::SeedableRng;
+
+  #[test]
+  fn reads_the_tuning_parameters() {
+    let file_path = concat!(env!("CARGO_MANIFEST_DIR"), "/tuning/parameters.txt");
+    let space = ParameterSpace::read_from(file_path).unwrap();
+    let real = |name: &str, lower: f64, upper: f64| Parameter {
+      name: name.to_string(),
+      switch: String::new(),
+      domain: Domain::Real {
+        lower,
+        upper,
+        log: false,
+      },
+    };
+    assert_eq!(
+      space.parameters,
+      vec![
+        real("sa_max_k", 0.1, 8.0),
+        real("alpha", 0.8, 1.0),
+        real("initial_temp", 100.0, 1000000.0),
+      ]
+    );
+    let values = [
+      Value::Real(3.8272),
+      Value::Real(0.9809),
+      Value::Real(10599.1518),
+    ];
+    assert_eq!(space.command_line(&values), "3.8272 0.9809 10599.1518");
+  }
+
+  #[test]
+  fn parses_every_kind_of_parameter() {
+    let space = ParameterSpace::read(
+      concat!(
+        "# comment\n",
+        "tenure \"--tenure \" i,log (1, 100)\n",
+        "\n",
+        "start \"--construct \" c (fcfs, \"edf\", lci) # heuristics\n",
+        "level \"--level=\" o (low, high)\n",
+      )
+      .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(
+      space.parameters[0].domain,
+      Domain::Integer {
+        lower: 1,
+        upper: 100,
+        log: true
+      }
+    );
+    let levels = |levels: &[&str]| levels.iter().map(|s| s.to_string()).collect();
+    assert_eq!(
+      space.parameters[1].domain,
+      Domain::Categorical(levels(&["fcfs", "edf", "lci"]))
+    );
+    assert_eq!(
+      space.parameters[2].domain,
+      Domain::Ordinal(levels(&["low", "high"]))
+    );
+    let values = [
+      Value::Integer(20),
+      Value::Label("edf".to_string()),
+      Value::Label("high".to_string()),
+    ];
+    assert_eq!(
+      space.command_line(&values),
+      "--tenure 20 --construct edf --level=high"
+    );
+    for row in [
+      "x \"\" r (2, 1)",
+      "x \"\" r,log (0, 1)",
+      "x \"\" c,log (a)",
+      "x r (0, 1)",
+    ] {
+      assert!(matches!(
+        ParameterSpace::read(row.as_bytes()),
+        Err(ParameterError::Format { line: 1, .. })
+      ));
+    }
+  }
+
+  /// Level `a` and 3 cost the least, whatever the instance
+  fn cost(values: &[Value], instance: usize, _seed: u64) -> f64 {
+    let level = match &values[0] {
+      Value::Label(label) => ["a", "b", "c", "d"]
+        .iter()
+        .position(|l| l == label)
+        .unwrap(),
+      value => panic!("unexpected {}", value),
+    };
+    let Value::Integer(x) = values[1] else {
+      panic!("unexpected {}", values[1]);
+    };
+    10.0 * level as f64 + (x - 3).abs() as f64 + instance as f64
+  }
+
+  #[test]
+  fn races_to_the_best_configuration() {
+    let space =
+      ParameterSpace::read("level \"\" c (a, b, c, d)\nx \"\" i (0, 10)\n".as_bytes()).unwrap();
+    let settings = RaceSettings {
+      budget: 400,
+      ..RaceSettings::default()
+    };
+    let race = |workers: usize| {
+      let mut rng = StdRng::seed_from_u64(1);
+      iterated_race(&space, 4, settings, workers, &mut rng, cost)
+    };
+    let elites = race(1);
+    assert_eq!(
+      elites[0].values,
+      vec![Value::Label("a".to_string()), Value::Integer(3)]
+    );
+    assert!(elites.len() <= 3);
+    assert_eq!(race(4), elites);
+    let mut rng = StdRng::seed_from_u64(1);
+    assert!(iterated_race(&space, 0, settings, 1, &mut rng, cost).is_empty());
+  }
+}
@@ -0,0 +1,227 @@
+use crate::metaheuristics::Problem;
+use crate::problem::{Arrival, LandingProblem, Runway, Solution, ID};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// An arrival of a solution, as written in reports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlaneReport {
+  pub id: ID,
+  pub runway: Runway,
+  pub landing_time: u32,
+  /// Landing window, taking the horizon into account
+  pub earliest: u32,
+  pub latest: u32,
+  pub target: u32,
+  /// Landing time minus target time
+  pub deviation: i64,
+  pub cost: f64,
+  /// Whether the arrival is in at least one conflict
+  pub conflict: bool,
+  /// Time since the previous arrival on the same runway, if any
+  pub separation_previous: Option<u32>,
+  /// Time until the next arrival on the same runway, if any
+  pub separation_next: Option<u32>,
+  /// Separation needed from the previous arrival on the same runway, if any,
+  /// in conflict when above `separation_previous`
+  pub required_separation_previous: Option<u32>,
+  /// Separation needed before the next arrival on the same runway, if any
+  pub required_separation_next: Option<u32>,
+}
+
+/// A solution with its costs and how it was found, for machine-readable
+/// output
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SolutionReport {
+  pub instance: String,
+  pub total_cost: f64,
+  pub landing_cost: f64,
+  pub conflict_cost: f64,
+  pub valid: bool,
+  /// Seconds
+  pub runtime: f64,
+  pub seed: u64,
+  /// In landing order
+  pub planes: Vec<PlaneReport>,
+}
+
+const CSV_HEADER: &str = concat!(
+  "instance,total_cost,landing_cost,conflict_cost,valid,runtime,seed,",
+  "id,runway,landing_time,earliest,latest,target,deviation,cost,conflict,",
+  "separation_previous,separation_next,",
+  "required_separation_previous,required_separation_next"
+);
+
+impl SolutionReport {
+  pub fn new(
+    instance: &str,
+    problem: &LandingProblem,
+    solution: &Solution,
+    runtime: Duration,
+    seed: u64,
+  ) -> Self {
+    let conflicts = problem
+      .conflicts(solution)
+      .flat_map(|(a, b, _)| [a, b])
+      .collect::<HashSet<_>>();
+    let planes = solution
+      .iter()
+      .enumerate()
+      .map(|(i, arrival)| {
+        let plane = &problem.planes[arrival.plane_id];
+        let window = problem.landing_window(arrival.plane_id);
+        let same_runway = |other: &&Arrival| other.runway == arrival.runway;
+        let previous = solution[..i].iter().rev().find(same_runway);
+        let next = solution[i + 1..].iter().find(same_runway);
+        PlaneReport {
+          id: plane.id,
+          runway: arrival.runway,
+          landing_time: arrival.landing_time,
+          earliest: *window.start(),
+          latest: *window.end(),
+          target: plane.target_landing,
+          deviation: arrival.landing_time as i64 - plane.target_landing as i64,
+          cost: plane.cost_for_landing(arrival.landing_time),
+          conflict: conflicts.contains(arrival),
+          separation_previous: previous.map(|x| arrival.landing_time - x.landing_time),
+          separation_next: next.map(|y| y.landing_time - arrival.landing_time),
+          required_separation_previous: previous.map(|x| problem.separation_between(x, arrival)),
+          required_separation_next: next.map(|y| problem.separation_between(arrival, y)),
+        }
+      })
+      .collect();
+    SolutionReport {
+      instance: instance.to_string(),
+      total_cost: problem.cost(solution),
+      landing_cost: problem.landing_cost(solution),
+      conflict_cost: problem.conflict_cost(solution),
+      valid: problem.is_valid(solution),
+      runtime: runtime.as_secs_f64(),
+      seed,
+      planes,
+    }
+  }
+
+  /// Writes the report as a JSON object on a single line, so that reports of
+  /// several runs can follow each other as JSON Lines
+  pub fn write_json(&self, writer: &mut impl Write) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, self)?;
+    writeln!(writer)
+  }
+
+  pub fn write_csv_header(writer: &mut impl Write) -> io::Result<()> {
+    writeln!(writer, "{}", CSV_HEADER)
+  }
+
+  /// Writes a CSV row per arrival, repeating the run fields in each, without
+  /// the header
+  pub fn write_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+    let optional = |value: Option<u32>| value.map_or(String::new(), |v| v.to_string());
+    for plane in &self.planes {
+      writeln!(
+        writer,
+        "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+        csv_field(&self.instance),
+        self.total_cost,
+        self.landing_cost,
+        self.conflict_cost,
+        self.valid,
+        self.runtime,
+        self.seed,
+        plane.id,
+        plane.runway,
+        plane.landing_time,
+        plane.earliest,
+        plane.latest,
+        plane.target,
+        plane.deviation,
+        plane.cost,
+        plane.conflict,
+        optional(plane.separation_previous),
+        optional(plane.separation_next),
+        optional(plane.required_separation_previous),
+        optional(plane.required_separation_next)
+      )?;
+    }
+    Ok(())
+  }
+}
+
+/// `value`, quoted if it holds a separator or a quote
+fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\n']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parser::{parse_problem, ParseMode};
+
+  /// Plane 2 needs 10 after plane 0 on the same runway
+  const INSTANCE: &str = concat!(
+    "3 10\n",
+    "0 10 15 30 1 1\n99999 5 10\n",
+    "0 10 20 40 2 3\n3 99999 2\n",
+    "0 10 25 50 1 1\n3 3 99999\n",
+  );
+
+  fn report() -> SolutionReport {
+    let data = parse_problem(INSTANCE.as_bytes(), ParseMode::Strict).unwrap();
+    let problem = LandingProblem::from_parser(data).with_runways(2);
+    let solution = vec![
+      Arrival::on_runway(0, 15, 0),
+      Arrival::on_runway(1, 18, 1),
+      Arrival::on_runway(2, 22, 0),
+    ];
+    SolutionReport::new("small", &problem, &solution, Duration::from_millis(1500), 7)
+  }
+
+  #[test]
+  fn writes_json_lines() {
+    let mut json = Vec::new();
+    report().write_json(&mut json).unwrap();
+    report().write_json(&mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    let line = concat!(
+      r#"{"instance":"small","total_cost":15007.0,"landing_cost":7.0,"#,
+      r#""conflict_cost":15000.0,"valid":false,"runtime":1.5,"seed":7,"planes":["#,
+      r#"{"id":0,"runway":0,"landing_time":15,"earliest":10,"latest":30,"target":15,"#,
+      r#""deviation":0,"cost":0.0,"conflict":true,"separation_previous":null,"#,
+      r#""separation_next":7,"required_separation_previous":null,"#,
+      r#""required_separation_next":10},"#,
+      r#"{"id":1,"runway":1,"landing_time":18,"earliest":10,"latest":40,"target":20,"#,
+      r#""deviation":-2,"cost":4.0,"conflict":false,"separation_previous":null,"#,
+      r#""separation_next":null,"required_separation_previous":null,"#,
+      r#""required_separation_next":null},"#,
+      r#"{"id":2,"runway":0,"landing_time":22,"earliest":10,"latest":50,"target":25,"#,
+      r#""deviation":-3,"cost":3.0,"conflict":true,"separation_previous":7,"#,
+      r#""separation_next":null,"required_separation_previous":10,"#,
+      r#""required_separation_next":null}]}"#,
+    );
+    assert_eq!(json, format!("{}\n{}\n", line, line));
+  }
+
+  #[test]
+  fn writes_csv_rows() {
+    let mut csv = Vec::new();
+    SolutionReport::write_csv_header(&mut csv).unwrap();
+    report().write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows[0], CSV_HEADER);
+    assert_eq!(
+      rows[1..],
+      [
+        "small,15007,7,15000,false,1.5,7,0,0,15,10,30,15,0,0,true,,7,,10",
+        "small,15007,7,15000,false,1.5,7,1,1,18,10,40,20,-2,4,false,,,,",
+        "small,15007,7,15000,false,1.5,7,2,0,22,10,50,25,-3,3,true,7,,10,",
+      ]
+    );
+  }
+}
@@ -0,0 +1,139 @@
+use crate::metaheuristics::Problem;
+use crate::problem::{LandingProblem, Solution};
+
+#[derive(Debug)]
+pub struct SimulationReport {
+  /// Schedule once every plane has appeared
+  pub schedule: Solution,
+  /// Number of times the landing of each plane changed after it was first
+  /// scheduled, indexed by plane id
+  pub reschedules: Vec<usize>,
+  /// Sum of the landing time changes over every reschedule
+  pub total_displacement: u64,
+  /// Number of times the schedule was optimised
+  pub events: usize,
+}
+
+/// Replays `problem` as planes appear: at every appearance time, the known
+/// planes are rescheduled by `solve`, starting from the current schedule with
+/// the new planes added, while landings within the freeze time stay fixed.
+/// `problem` is left without a horizon
+pub fn simulate<F>(problem: &mut LandingProblem, mut solve: F) -> SimulationReport
+where
+  F: FnMut(&LandingProblem, &Solution) -> Solution,
+{
+  let mut appearances: Vec<_> = problem.planes.iter().map(|p| p.appearance_time).collect();
+  appearances.sort();
+  appearances.dedup();
+
+  let mut report = SimulationReport {
+    schedule: Vec::new(),
+    reschedules: vec![0; problem.planes.len()],
+    total_displacement: 0,
+    events: appearances.len(),
+  };
+  for now in appearances {
+    problem.set_horizon(now, &report.schedule);
+
+    let mut known = vec![None; problem.planes.len()];
+    for arrival in &report.schedule {
+      known[arrival.plane_id] = Some(*arrival);
+    }
+    let mut start = report.schedule.clone();
+    start.extend(
+      problem
+        .initial_solution()
+        .into_iter()
+        .filter(|arrival| known[arrival.plane_id].is_none()),
+    );
+    start.sort_by_key(|a| a.landing_time);
+
+    let schedule = solve(problem, &start);
+    for arrival in &schedule {
+      if let Some(previous) = known[arrival.plane_id] {
+        if previous != *arrival {
+          report.reschedules[arrival.plane_id] += 1;
+          report.total_displacement += previous.landing_time.abs_diff(arrival.landing_time) as u64;
+        }
+      }
+    }
+    report.schedule = schedule;
+  }
+  problem.horizon = None;
+  report
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::generator::{generate, GeneratorSettings};
+  use crate::metaheuristics::{simulated_annealing, tabu_search, IncrementalProblem};
+  use crate::problem::Arrival;
+  use rand::rngs::StdRng;
+  use rand::SeedableRng;
+
+  fn instance() -> LandingProblem {
+    let settings = GeneratorSettings {
+      planes: 15,
+      target_spread: 300,
+      freeze_time: 40,
+      ..GeneratorSettings::default()
+    };
+    let data = generate(&settings, &mut StdRng::seed_from_u64(3)).unwrap();
+    LandingProblem::from_parser(data).with_runways(2)
+  }
+
+  #[test]
+  fn frozen_arrivals_stay_fixed() {
+    let mut problem = instance();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut previous: Vec<Option<Arrival>> = vec![None; problem.planes.len()];
+    let mut reschedules = vec![0; problem.planes.len()];
+    let mut displacement = 0;
+    let mut frozen_count = 0;
+    let report = simulate(&mut problem, |problem, start| {
+      let schedule = simulated_annealing(problem, start, 2000, 0.95, 50, 1000.0, &mut rng);
+      assert!(schedule.is_sorted_by_key(|a| a.landing_time));
+      let mut ids: Vec<_> = schedule.iter().map(|a| a.plane_id).collect();
+      ids.sort();
+      let appeared: Vec<_> = problem.scheduled_planes().map(|p| p.id).collect();
+      assert_eq!(ids, appeared);
+      for arrival in &schedule {
+        if let Some(frozen) = problem.frozen(arrival.plane_id) {
+          assert_eq!(arrival, frozen);
+          frozen_count += 1;
+        }
+        if let Some(before) = previous[arrival.plane_id] {
+          if before != *arrival {
+            reschedules[arrival.plane_id] += 1;
+            displacement += before.landing_time.abs_diff(arrival.landing_time) as u64;
+          }
+        }
+        previous[arrival.plane_id] = Some(*arrival);
+      }
+      schedule
+    });
+    assert!(frozen_count > 0, "the instance should freeze some landings");
+    assert!(problem.horizon.is_none());
+
+    let mut appearances: Vec<_> = problem.planes.iter().map(|p| p.appearance_time).collect();
+    appearances.sort();
+    appearances.dedup();
+    assert_eq!(report.events, appearances.len());
+    assert_eq!(report.reschedules, reschedules);
+    assert_eq!(report.total_displacement, displacement);
+    assert!(report.reschedules.iter().all(|&r| r < report.events));
+    assert_eq!(report.schedule.len(), problem.planes.len());
+    assert!(problem.is_valid(&report.schedule));
+  }
+
+  #[test]
+  fn nothing_to_move_before_planes_appear() {
+    let problem = instance();
+    let mut rng = StdRng::seed_from_u64(0);
+    let empty = Vec::new();
+    assert!(problem.random_move(&empty, &mut rng).is_none());
+    assert!(simulated_annealing(&problem, &empty, 100, 0.9, 10, 10.0, &mut rng).is_empty());
+    assert!(tabu_search(&problem, &empty, 100, 5, 10, &mut rng).is_empty());
+  }
+}
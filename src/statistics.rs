@@ -0,0 +1,437 @@
+use std::f64::consts::SQRT_2;
+
+/// Differences smaller than this are ties
+const EPSILON: f64 = 1e-9;
+
+/// Largest number of pairs for which the Wilcoxon distribution is enumerated
+/// instead of approximated
+const MAX_EXACT_PAIRS: usize = 25;
+
+/// Complementary error function, with a relative error below 1.2e-7
+/// (Numerical Recipes' `erfcc`)
+fn erfc(x: f64) -> f64 {
+  let z = x.abs();
+  let t = 1.0 / (1.0 + 0.5 * z);
+  let coefficients = [
+    -1.26551223,
+    1.00002368,
+    0.37409196,
+    0.09678418,
+    -0.18628806,
+    0.27886807,
+    -1.13520398,
+    1.48851587,
+    -0.82215223,
+    0.17087277,
+  ];
+  let polynomial = coefficients.iter().rev().fold(0.0, |sum, c| sum * t + c);
+  let value = t * (-z * z + polynomial).exp();
+  if x >= 0.0 {
+    value
+  } else {
+    2.0 - value
+  }
+}
+
+pub fn normal_cdf(x: f64) -> f64 {
+  0.5 * erfc(-x / SQRT_2)
+}
+
+/// Logarithm of the gamma function (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+  let coefficients = [
+    76.18009172947146,
+    -86.50532032941677,
+    24.01409824083091,
+    -1.231739572450155,
+    0.1208650973866179e-2,
+    -0.5395239384953e-5,
+  ];
+  let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+  let series = coefficients
+    .iter()
+    .enumerate()
+    .fold(1.000000000190015, |sum, (k, c)| {
+      sum + c / (x + 1.0 + k as f64)
+    });
+  -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x), by its series below
+/// `a + 1` and its continued fraction above
+fn gamma_q(a: f64, x: f64) -> f64 {
+  if x <= 0.0 {
+    return 1.0;
+  }
+  let ln_prefactor = -x + a * x.ln() - ln_gamma(a);
+  if x < a + 1.0 {
+    let (mut term, mut sum) = (1.0 / a, 1.0 / a);
+    for n in 1..1000 {
+      term *= x / (a + n as f64);
+      sum += term;
+      if term.abs() < sum.abs() * 1e-15 {
+        break;
+      }
+    }
+    1.0 - sum * ln_prefactor.exp()
+  } else {
+    // Modified Lentz's method
+    let tiny = 1e-300;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for n in 1..1000 {
+      let an = -(n as f64) * (n as f64 - a);
+      b += 2.0;
+      d = an * d + b;
+      d = if d.abs() < tiny { tiny } else { d };
+      c = b + an / c;
+      c = if c.abs() < tiny { tiny } else { c };
+      d = 1.0 / d;
+      let delta = d * c;
+      h *= delta;
+      if (delta - 1.0).abs() < 1e-15 {
+        break;
+      }
+    }
+    h * ln_prefactor.exp()
+  }
+}
+
+/// Probability of a chi-squared variable with `df` degrees of freedom being
+/// at least `x`
+pub fn chi_squared_sf(x: f64, df: f64) -> f64 {
+  gamma_q(df / 2.0, x / 2.0)
+}
+
+/// 1-based ranks of `values`, ties getting the mean of their ranks, and the
+/// sizes of the groups of ties
+fn ranks(values: &[f64]) -> (Vec<f64>, Vec<usize>) {
+  let mut order: Vec<usize> = (0..values.len()).collect();
+  order.sort_by(|&i, &j| values[i].partial_cmp(&values[j]).unwrap());
+  let mut ranks = vec![0.0; values.len()];
+  let mut ties = Vec::new();
+  let mut start = 0;
+  while start < order.len() {
+    let mut end = start + 1;
+    while end < order.len() && values[order[end]] - values[order[start]] <= EPSILON {
+      end += 1;
+    }
+    let rank = (start + end + 1) as f64 / 2.0;
+    for &k in &order[start..end] {
+      ranks[k] = rank;
+    }
+    ties.push(end - start);
+    start = end;
+  }
+  (ranks, ties)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wilcoxon {
+  /// Pairs that differ
+  pub n: usize,
+  /// Sum of the ranks of the pairs where the first sample is larger
+  pub w_plus: f64,
+  pub w_minus: f64,
+  /// Two-sided
+  pub p_value: f64,
+}
+
+/// Wilcoxon signed-rank test of paired samples `a` and `b`, leaving out equal
+/// pairs. The p-value is exact for up to `MAX_EXACT_PAIRS` pairs without ties,
+/// and from the normal approximation with tie and continuity corrections
+/// otherwise
+pub fn wilcoxon_signed_rank(a: &[f64], b: &[f64]) -> Wilcoxon {
+  assert_eq!(a.len(), b.len());
+  let differences: Vec<f64> = a
+    .iter()
+    .zip(b)
+    .map(|(x, y)| x - y)
+    .filter(|d| d.abs() > EPSILON)
+    .collect();
+  let n = differences.len();
+  let (ranks, ties) = ranks(&differences.iter().map(|d| d.abs()).collect::<Vec<_>>());
+  let w_plus: f64 = (0..n)
+    .filter(|&k| differences[k] > 0.0)
+    .fold(0.0, |sum, k| sum + ranks[k]);
+  let w_minus = (n * (n + 1)) as f64 / 2.0 - w_plus;
+  let p_value = if n == 0 {
+    1.0
+  } else if n <= MAX_EXACT_PAIRS && ties.iter().all(|&t| t == 1) {
+    // Number of subsets of 1..=n with each rank sum
+    let total = n * (n + 1) / 2;
+    let mut counts = vec![0.0f64; total + 1];
+    counts[0] = 1.0;
+    for rank in 1..=n {
+      for sum in (rank..=total).rev() {
+        counts[sum] += counts[sum - rank];
+      }
+    }
+    let smallest = w_plus.min(w_minus) as usize;
+    let tail: f64 = counts[..=smallest].iter().sum();
+    (2.0 * tail / 2f64.powi(n as i32)).min(1.0)
+  } else {
+    let n = n as f64;
+    let mean = n * (n + 1.0) / 4.0;
+    let correction: f64 = ties.iter().map(|&t| (t.pow(3) - t) as f64).sum::<f64>() / 48.0;
+    let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - correction;
+    let z = ((w_plus - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    (2.0 * (1.0 - normal_cdf(z))).min(1.0)
+  };
+  Wilcoxon {
+    n,
+    w_plus,
+    w_minus,
+    p_value,
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Friedman {
+  /// Chi-squared statistic, corrected for ties
+  pub statistic: f64,
+  pub df: usize,
+  pub p_value: f64,
+  /// Mean rank of every treatment over the blocks, 1 being the lowest value
+  pub mean_ranks: Vec<f64>,
+  pub blocks: usize,
+}
+
+/// Friedman test of `blocks`, each holding the value of every treatment.
+/// Without blocks, with a single treatment, or with every block tied, there
+/// is no difference to test: the statistic is 0 and the p-value 1
+pub fn friedman(blocks: &[Vec<f64>]) -> Friedman {
+  let k = blocks.first().map_or(0, Vec::len);
+  let n = blocks.len() as f64;
+  let mut rank_sums = vec![0.0; k];
+  let mut ties = 0.0;
+  for block in blocks {
+    assert_eq!(block.len(), k);
+    let (ranks, groups) = ranks(block);
+    for (sum, rank) in rank_sums.iter_mut().zip(ranks) {
+      *sum += rank;
+    }
+    ties += groups.iter().map(|&t| (t.pow(3) - t) as f64).sum::<f64>();
+  }
+  let k_f = k as f64;
+  if blocks.is_empty() || k < 2 {
+    return Friedman {
+      statistic: 0.0,
+      df: k.saturating_sub(1),
+      p_value: 1.0,
+      mean_ranks: vec![1.0; k],
+      blocks: blocks.len(),
+    };
+  }
+  let statistic = 12.0 / (n * k_f * (k_f + 1.0)) * rank_sums.iter().map(|r| r * r).sum::<f64>()
+    - 3.0 * n * (k_f + 1.0);
+  let denominator = 1.0 - ties / (n * (k_f.powi(3) - k_f));
+  let statistic = if denominator > EPSILON {
+    statistic / denominator
+  } else {
+    0.0
+  };
+  Friedman {
+    statistic,
+    df: k - 1,
+    p_value: chi_squared_sf(statistic, (k - 1) as f64),
+    mean_ranks: rank_sums.iter().map(|sum| sum / n).collect(),
+    blocks: blocks.len(),
+  }
+}
+
+/// Holm's step-down adjustment of `p_values`
+pub fn holm(p_values: &[f64]) -> Vec<f64> {
+  let m = p_values.len();
+  let mut order: Vec<usize> = (0..m).collect();
+  order.sort_by(|&i, &j| p_values[i].partial_cmp(&p_values[j]).unwrap());
+  let mut adjusted = vec![0.0; m];
+  let mut largest: f64 = 0.0;
+  for (step, &k) in order.iter().enumerate() {
+    largest = largest.max(((m - step) as f64 * p_values[k]).min(1.0));
+    adjusted[k] = largest;
+  }
+  adjusted
+}
+
+/// Post-hoc comparison of every pair of treatments after `friedman`: z-tests
+/// on their mean ranks (Demšar, 2006), adjusted with `holm`. Indexed like
+/// the treatments, the diagonal being 1
+pub fn friedman_post_hoc(friedman: &Friedman) -> Vec<Vec<f64>> {
+  let k = friedman.mean_ranks.len();
+  let k_f = k as f64;
+  let standard_error = (k_f * (k_f + 1.0) / (6.0 * friedman.blocks as f64)).sqrt();
+  let pairs: Vec<(usize, usize)> = (0..k)
+    .flat_map(|i| (i + 1..k).map(move |j| (i, j)))
+    .collect();
+  let p_values: Vec<f64> = pairs
+    .iter()
+    .map(|&(i, j)| {
+      let z = (friedman.mean_ranks[i] - friedman.mean_ranks[j]).abs() / standard_error;
+      2.0 * (1.0 - normal_cdf(z))
+    })
+    .collect();
+  let mut table = vec![vec![1.0; k]; k];
+  for (&(i, j), p_value) in pairs.iter().zip(holm(&p_values)) {
+    table[i][j] = p_value;
+    table[j][i] = p_value;
+  }
+  table
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+      (actual - expected).abs() <= tolerance,
+      "{} is not within {} of {}",
+      actual,
+      tolerance,
+      expected
+    );
+  }
+
+  /// Pairs whose differences have the ranks 1..=n, negative for the ranks in
+  /// `negative`
+  fn signed_ranks(n: usize, negative: &[usize]) -> (Vec<f64>, Vec<f64>) {
+    let a = (1..=n)
+      .map(|rank| {
+        if negative.contains(&rank) {
+          -(rank as f64)
+        } else {
+          rank as f64
+        }
+      })
+      .collect();
+    (a, vec![0.0; n])
+  }
+
+  #[test]
+  fn normal_and_chi_squared_tables() {
+    assert_close(normal_cdf(0.0), 0.5, 1e-7);
+    assert_close(normal_cdf(1.959964), 0.975, 1e-6);
+    assert_close(normal_cdf(-2.575829), 0.005, 1e-6);
+    assert_close(chi_squared_sf(3.841459, 1.0), 0.05, 1e-6);
+    assert_close(chi_squared_sf(7.814728, 3.0), 0.05, 1e-6);
+    assert_close(chi_squared_sf(11.344867, 3.0), 0.01, 1e-6);
+    assert_close(chi_squared_sf(18.307038, 10.0), 0.05, 1e-6);
+    // With 2 degrees of freedom, the survival function is exp(-x / 2)
+    assert_close(chi_squared_sf(5.0, 2.0), (-2.5f64).exp(), 1e-12);
+  }
+
+  #[test]
+  fn wilcoxon_exact_critical_values() {
+    // Two-sided critical values at 0.05: T = 0 for n = 6, 3 for n = 8 and 8
+    // for n = 10, out of 2^n equally likely rank sums
+    let cases: [(usize, &[usize], f64); 5] = [
+      (6, &[], 2.0 / 64.0),
+      (8, &[3], 10.0 / 256.0),
+      (8, &[4], 14.0 / 256.0),
+      (10, &[8], 50.0 / 1024.0),
+      (10, &[9], 66.0 / 1024.0),
+    ];
+    for (n, negative, p_value) in cases {
+      let (a, b) = signed_ranks(n, negative);
+      let test = wilcoxon_signed_rank(&a, &b);
+      let w_minus: usize = negative.iter().sum();
+      assert_eq!(test.n, n);
+      assert_eq!(test.w_minus, w_minus as f64);
+      assert_eq!(test.w_plus, (n * (n + 1) / 2 - w_minus) as f64);
+      assert_close(test.p_value, p_value, 1e-12);
+    }
+  }
+
+  #[test]
+  fn wilcoxon_leaves_out_equal_pairs() {
+    let test = wilcoxon_signed_rank(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]);
+    assert_eq!(test.n, 0);
+    assert_eq!(test.p_value, 1.0);
+    let (mut a, mut b) = signed_ranks(6, &[]);
+    a.push(4.0);
+    b.push(4.0);
+    assert_close(wilcoxon_signed_rank(&a, &b).p_value, 2.0 / 64.0, 1e-12);
+  }
+
+  #[test]
+  fn wilcoxon_normal_approximation_with_ties() {
+    // Differences 1, 1, 2, 2, 3: ranks 1.5, 1.5, 3.5, 3.5, 5, the last negative
+    let test = wilcoxon_signed_rank(&[1.0, 1.0, 2.0, 2.0, 0.0], &[0.0, 0.0, 0.0, 0.0, 3.0]);
+    assert_eq!(test.w_plus, 10.0);
+    assert_eq!(test.w_minus, 5.0);
+    // Mean 7.5, variance 13.75 - 12 / 48 = 13.5
+    let z = (2.5 - 0.5) / 13.5f64.sqrt();
+    assert_close(test.p_value, 2.0 * (1.0 - normal_cdf(z)), 1e-12);
+  }
+
+  #[test]
+  fn friedman_demsar_example() {
+    // Ranks of the AUCs of C4.5, C4.5+m, C4.5+cf and C4.5+m+cf on 14 data
+    // sets, Table 6 of Demšar (2006)
+    let blocks: Vec<Vec<f64>> = [
+      [4.0, 3.0, 2.0, 1.0],
+      [1.0, 2.0, 3.0, 4.0],
+      [4.0, 1.0, 2.0, 3.0],
+      [4.0, 1.0, 3.0, 2.0],
+      [4.0, 2.0, 3.0, 1.0],
+      [1.0, 2.5, 4.0, 2.5],
+      [3.0, 2.0, 4.0, 1.0],
+      [2.5, 2.5, 4.0, 1.0],
+      [4.0, 3.0, 2.0, 1.0],
+      [2.5, 2.5, 2.5, 2.5],
+      [4.0, 2.5, 1.0, 2.5],
+      [3.0, 2.0, 4.0, 1.0],
+      [4.0, 1.0, 2.0, 3.0],
+      [3.0, 1.0, 4.0, 2.0],
+    ]
+    .iter()
+    .map(|block| block.to_vec())
+    .collect();
+    let test = friedman(&blocks);
+    for (rank, expected) in test.mean_ranks.iter().zip([3.143, 2.000, 2.893, 1.964]) {
+      assert_close(*rank, expected, 5e-4);
+    }
+    assert_eq!(test.df, 3);
+    assert_eq!(test.blocks, 14);
+    // 9.28 without the tie correction, divided by 1 - 78 / (14 * 60)
+    assert_close(test.statistic, 9.278571 / (1.0 - 78.0 / 840.0), 1e-5);
+    assert_close(test.p_value, chi_squared_sf(test.statistic, 3.0), 1e-12);
+    assert!(test.p_value < 0.05);
+  }
+
+  #[test]
+  fn friedman_without_differences() {
+    let one_treatment = friedman(&[vec![3.0], vec![1.0], vec![2.0]]);
+    assert_eq!(one_treatment.statistic, 0.0);
+    assert_eq!(one_treatment.df, 0);
+    assert_eq!(one_treatment.p_value, 1.0);
+    assert_eq!(one_treatment.mean_ranks, vec![1.0]);
+    let no_blocks = friedman(&[]);
+    assert_eq!(no_blocks.statistic, 0.0);
+    assert_eq!(no_blocks.p_value, 1.0);
+    assert_eq!(no_blocks.blocks, 0);
+    assert!(no_blocks.mean_ranks.is_empty());
+    // Treatments ranked 1 to k on a single block give k - 1
+    let one_block = friedman(&[vec![3.0, 1.0, 2.0]]);
+    assert_close(one_block.statistic, 2.0, 1e-12);
+    assert_close(one_block.p_value, (-1.0f64).exp(), 1e-9);
+    assert_eq!(one_block.mean_ranks, vec![3.0, 1.0, 2.0]);
+    let tied = friedman(&[vec![1.0, 1.0, 1.0], vec![2.0, 2.0, 2.0]]);
+    assert_eq!(tied.statistic, 0.0);
+    assert_eq!(tied.p_value, 1.0);
+    assert_eq!(tied.mean_ranks, vec![2.0; 3]);
+  }
+
+  #[test]
+  fn holm_adjustment() {
+    let adjusted = holm(&[0.01, 0.04, 0.03, 0.005]);
+    let expected = [0.03, 0.06, 0.06, 0.02];
+    for (p_value, expected) in adjusted.iter().zip(expected) {
+      assert_close(*p_value, expected, 1e-12);
+    }
+    assert_eq!(holm(&[0.5, 0.9]), vec![1.0, 1.0]);
+  }
+}
@@ -0,0 +1,532 @@
+use crate::problem::{Arrival, LandingProblem, Runway, Solution, CONFLICT_PENALTY, ID};
+use std::collections::VecDeque;
+use std::{error, fmt};
+
+const EPSILON: f64 = 1e-9;
+
+/// A landing order that cannot be scheduled: even landing every plane as
+/// early as possible, `plane_id` would land after its latest landing time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InfeasibleOrder {
+  pub plane_id: ID,
+  /// Earliest time the plane could land in this order
+  pub earliest: u32,
+}
+
+impl fmt::Display for InfeasibleOrder {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "plane #{} cannot land before {}, after its latest landing time",
+      self.plane_id, self.earliest
+    )
+  }
+}
+
+impl error::Error for InfeasibleOrder {}
+
+impl LandingProblem {
+  /// Cost-minimal landing times for planes landing in `order`. With several
+  /// runways, each plane goes to the runway where it can land the earliest
+  pub fn timing(&self, order: &[ID]) -> Result<Solution, InfeasibleOrder> {
+    let (sequence, _) = self.earliest_in_order(order);
+    self.timing_on_runways(&sequence)
+  }
+
+  /// Cost of landing in `order` as early as possible, every unit of time a
+  /// plane lands after its latest landing time costing `CONFLICT_PENALTY`.
+  /// Finite for infeasible orders, and lower the closer they are to
+  /// feasibility
+  pub fn penalized_cost(&self, order: &[ID]) -> f64 {
+    let (sequence, earliest) = self.earliest_in_order(order);
+    sequence
+      .iter()
+      .zip(earliest)
+      .map(|(&(plane_id, _), time)| {
+        let excess = time.saturating_sub(*self.landing_window(plane_id).end());
+        self.planes[plane_id].cost_for_landing(time) + CONFLICT_PENALTY * excess as f64
+      })
+      .sum()
+  }
+
+  /// Runways of the planes landing in `order`, each one where the plane can
+  /// land the earliest, with these earliest landing times, which can be after
+  /// the latest landing times
+  fn earliest_in_order(&self, order: &[ID]) -> (Vec<(ID, Runway)>, Vec<u32>) {
+    let mut sequence: Vec<(ID, Runway)> = Vec::with_capacity(order.len());
+    let mut earliest: Vec<u32> = Vec::with_capacity(order.len());
+    for &plane_id in order {
+      let (runway, time) = self
+        .allowed_runways(plane_id)
+        .map(|runway| {
+          sequence.push((plane_id, runway));
+          let time = self.earliest_landing_in_sequence(&sequence, &earliest);
+          sequence.pop();
+          (runway, time)
+        })
+        .min_by_key(|&(_, time)| time)
+        .unwrap();
+      sequence.push((plane_id, runway));
+      earliest.push(time);
+    }
+    (sequence, earliest)
+  }
+
+  /// Cost-minimal landing times for planes landing in the order and on the
+  /// runways given by `sequence`.
+  ///
+  /// The landing order turns separation into difference constraints between
+  /// landing times, so the costs being convex, a schedule is optimal when no
+  /// set of planes can be delayed or advanced together by one unit to lower
+  /// the cost. Starting from the earliest schedule, such sets are found as a
+  /// minimum closure with a max-flow, then moved until the cost slope changes
+  pub fn timing_on_runways(&self, sequence: &[(ID, Runway)]) -> Result<Solution, InfeasibleOrder> {
+    let mut times: Vec<u32> = Vec::with_capacity(sequence.len());
+    for (l, &(plane_id, _)) in sequence.iter().enumerate() {
+      let time = self.earliest_landing_in_sequence(&sequence[..=l], &times);
+      if time > *self.landing_window(plane_id).end() {
+        return Err(InfeasibleOrder {
+          plane_id,
+          earliest: time,
+        });
+      }
+      times.push(time);
+    }
+
+    let mut timing = Timing {
+      problem: self,
+      sequence,
+      times,
+    };
+    while timing.shift(Direction::Later) || timing.shift(Direction::Earlier) {}
+
+    let mut solution: Solution = sequence
+      .iter()
+      .zip(timing.times)
+      .map(|(&(plane_id, runway), time)| Arrival::on_runway(plane_id, time, runway))
+      .collect();
+    solution.sort_by_key(|a| a.landing_time);
+    Ok(solution)
+  }
+
+  /// Earliest landing time of the last plane in `sequence`, given the landing
+  /// times of the ones before it
+  pub(crate) fn earliest_landing_in_sequence(
+    &self,
+    sequence: &[(ID, Runway)],
+    times: &[u32],
+  ) -> u32 {
+    let (&(plane_id, runway), before) = sequence.split_last().unwrap();
+    let b = Arrival::on_runway(plane_id, 0, runway);
+    let mut time = *self.landing_window(plane_id).start();
+    // Related planes land in sequence order, so the scan can stop once they
+    // are too far back to constrain this one
+    for (&(plane_id, runway), &landing_time) in before.iter().zip(times).rev() {
+      let a = Arrival::on_runway(plane_id, landing_time, runway);
+      if !self.related(&a, &b) {
+        continue;
+      }
+      if landing_time + self.max_separation <= time {
+        break;
+      }
+      time = time.max(landing_time + self.separation_between(&a, &b));
+    }
+    time
+  }
+
+  /// Whether the landing order of `a` and `b` implies a separation
+  fn related(&self, a: &Arrival, b: &Arrival) -> bool {
+    a.runway == b.runway || self.cross_runway_separation.is_some()
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+  Earlier,
+  Later,
+}
+
+struct Timing<'a> {
+  problem: &'a LandingProblem,
+  sequence: &'a [(ID, Runway)],
+  times: Vec<u32>,
+}
+
+impl Timing<'_> {
+  fn arrival(&self, k: usize) -> Arrival {
+    let (plane_id, runway) = self.sequence[k];
+    Arrival::on_runway(plane_id, self.times[k], runway)
+  }
+
+  /// Calls `f(l, slack)` for every plane `l` after `k` whose separation from
+  /// `k` has less than `max_slack` units of slack
+  fn successors(&self, k: usize, max_slack: u32, mut f: impl FnMut(usize, u32)) {
+    let a = self.arrival(k);
+    for l in k + 1..self.sequence.len() {
+      let b = self.arrival(l);
+      if !self.problem.related(&a, &b) {
+        continue;
+      }
+      if b.landing_time >= a.landing_time + self.problem.max_separation + max_slack {
+        break;
+      }
+      let slack = b.landing_time - a.landing_time - self.problem.separation_between(&a, &b);
+      if slack < max_slack {
+        f(l, slack);
+      }
+    }
+  }
+
+  /// Moves the cheapest set of planes that can be shifted together in
+  /// `direction`, returns whether the cost went down
+  fn shift(&mut self, direction: Direction) -> bool {
+    let n = self.sequence.len();
+    let (source, sink) = (n, n + 1);
+    let mut network = FlowNetwork::new(n + 2);
+    let mut negative_weight = 0.0;
+    // Cost change per unit of time of every plane, and how far it can go
+    // before that changes
+    let mut ranges = Vec::with_capacity(n);
+    for k in 0..n {
+      let plane = &self.problem.planes[self.sequence[k].0];
+      let window = self.problem.landing_window(plane.id);
+      let time = self.times[k];
+      let (weight, range) = match direction {
+        Direction::Later if time < plane.target_landing => (
+          -plane.penalty_before,
+          plane.target_landing.min(*window.end()) - time,
+        ),
+        Direction::Later => (plane.penalty_after, window.end() - time),
+        Direction::Earlier if time > plane.target_landing => (
+          -plane.penalty_after,
+          time - plane.target_landing.max(*window.start()),
+        ),
+        Direction::Earlier => (plane.penalty_before, time - window.start()),
+      };
+      if range == 0 {
+        network.add_edge(k, sink, f64::INFINITY);
+      } else if weight < 0.0 {
+        network.add_edge(source, k, -weight);
+        negative_weight -= weight;
+      } else if weight > 0.0 {
+        network.add_edge(k, sink, weight);
+      }
+      ranges.push(range);
+
+      // Planes separated by exactly the minimum move together
+      self.successors(k, 1, |l, _| match direction {
+        Direction::Later => network.add_edge(k, l, f64::INFINITY),
+        Direction::Earlier => network.add_edge(l, k, f64::INFINITY),
+      });
+    }
+    if negative_weight < EPSILON {
+      return false;
+    }
+
+    let cut = network.max_flow(source, sink);
+    if cut > negative_weight - EPSILON {
+      return false;
+    }
+    let moved = network.source_side(source);
+
+    let mut step = (0..n)
+      .filter(|&k| moved[k])
+      .map(|k| ranges[k])
+      .min()
+      .unwrap();
+    for k in 0..n {
+      self.successors(k, step, |l, slack| {
+        let leaving = match direction {
+          Direction::Later => moved[k] && !moved[l],
+          Direction::Earlier => moved[l] && !moved[k],
+        };
+        if leaving {
+          step = step.min(slack);
+        }
+      });
+    }
+    for k in (0..n).filter(|&k| moved[k]) {
+      match direction {
+        Direction::Later => self.times[k] += step,
+        Direction::Earlier => self.times[k] -= step,
+      }
+    }
+    true
+  }
+}
+
+struct Edge {
+  to: usize,
+  capacity: f64,
+}
+
+/// Dinic's max-flow, only used to find minimum closures
+struct FlowNetwork {
+  graph: Vec<Vec<usize>>,
+  edges: Vec<Edge>,
+}
+
+impl FlowNetwork {
+  fn new(nodes: usize) -> Self {
+    FlowNetwork {
+      graph: vec![Vec::new(); nodes],
+      edges: Vec::new(),
+    }
+  }
+
+  fn add_edge(&mut self, from: usize, to: usize, capacity: f64) {
+    self.graph[from].push(self.edges.len());
+    self.edges.push(Edge { to, capacity });
+    self.graph[to].push(self.edges.len());
+    self.edges.push(Edge {
+      to: from,
+      capacity: 0.0,
+    });
+  }
+
+  fn levels(&self, source: usize) -> Vec<Option<usize>> {
+    let mut levels = vec![None; self.graph.len()];
+    levels[source] = Some(0);
+    let mut queue = VecDeque::from([source]);
+    while let Some(u) = queue.pop_front() {
+      for &e in &self.graph[u] {
+        let edge = &self.edges[e];
+        if edge.capacity > EPSILON && levels[edge.to].is_none() {
+          levels[edge.to] = levels[u].map(|level| level + 1);
+          queue.push_back(edge.to);
+        }
+      }
+    }
+    levels
+  }
+
+  fn augment(
+    &mut self,
+    u: usize,
+    sink: usize,
+    flow: f64,
+    levels: &[Option<usize>],
+    next: &mut [usize],
+  ) -> f64 {
+    if u == sink {
+      return flow;
+    }
+    while next[u] < self.graph[u].len() {
+      let e = self.graph[u][next[u]];
+      let Edge { to, capacity } = self.edges[e];
+      if capacity > EPSILON && levels[to] == levels[u].map(|level| level + 1) {
+        let pushed = self.augment(to, sink, flow.min(capacity), levels, next);
+        if pushed > EPSILON {
+          self.edges[e].capacity -= pushed;
+          self.edges[e ^ 1].capacity += pushed;
+          return pushed;
+        }
+      }
+      next[u] += 1;
+    }
+    0.0
+  }
+
+  fn max_flow(&mut self, source: usize, sink: usize) -> f64 {
+    let mut flow = 0.0;
+    loop {
+      let levels = self.levels(source);
+      if levels[sink].is_none() {
+        return flow;
+      }
+      let mut next = vec![0; self.graph.len()];
+      loop {
+        let pushed = self.augment(source, sink, f64::INFINITY, &levels, &mut next);
+        if pushed <= EPSILON {
+          break;
+        }
+        flow += pushed;
+      }
+    }
+  }
+
+  /// Nodes still reachable from `source` in the residual network
+  fn source_side(&self, source: usize) -> Vec<bool> {
+    self.levels(source).iter().map(Option::is_some).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parser::{self, ProblemData};
+  use rand::rngs::StdRng;
+  use rand::seq::SliceRandom;
+  use rand::{Rng, SeedableRng};
+
+  fn plane(earliest: u32, target: u32, latest: u32, penalties: (f64, f64)) -> parser::Plane {
+    parser::Plane {
+      appearance_time: 0,
+      earliest_landing: earliest,
+      target_landing: target,
+      latest_landing: latest,
+      penalty_before: penalties.0,
+      penalty_after: penalties.1,
+      separation_times: Vec::new(),
+    }
+  }
+
+  fn problem(mut planes: Vec<parser::Plane>, separation_times: Vec<Vec<u32>>) -> LandingProblem {
+    for (plane, separation_times) in planes.iter_mut().zip(separation_times) {
+      plane.separation_times = separation_times;
+    }
+    LandingProblem::from_parser(ProblemData {
+      num_planes: planes.len(),
+      freeze_time: 0,
+      planes,
+      replaced: Vec::new(),
+    })
+  }
+
+  /// A few planes with short windows, on up to 2 runways, with or without
+  /// separation between runways
+  fn random_problem(rng: &mut StdRng) -> LandingProblem {
+    let n = rng.gen_range(2..=5);
+    let planes = (0..n)
+      .map(|_| {
+        let earliest = rng.gen_range(0..10);
+        let target = earliest + rng.gen_range(0..=4);
+        let latest = target + rng.gen_range(0..=4);
+        let penalties = (rng.gen_range(1..=9) as f64, rng.gen_range(1..=9) as f64);
+        plane(earliest, target, latest, penalties)
+      })
+      .collect();
+    let mut separation = |max: u32| -> Vec<Vec<u32>> {
+      (0..n)
+        .map(|a| {
+          (0..n)
+            .map(|b| {
+              if a == b {
+                99999
+              } else {
+                rng.gen_range(0..=max)
+              }
+            })
+            .collect()
+        })
+        .collect()
+    };
+    let separation_times = separation(4);
+    let cross_runway_separation = separation(2);
+    let problem = problem(planes, separation_times).with_runways(rng.gen_range(1..=2));
+    if rng.gen_bool(0.5) {
+      problem.with_cross_runway_separation(cross_runway_separation)
+    } else {
+      problem
+    }
+  }
+
+  /// Lowest cost of landing times for `sequence`, trying every time of every
+  /// window, or `None` if no times are feasible
+  fn brute_force(problem: &LandingProblem, sequence: &[(ID, Runway)]) -> Option<f64> {
+    fn search(
+      problem: &LandingProblem,
+      sequence: &[(ID, Runway)],
+      arrivals: &mut Vec<Arrival>,
+    ) -> Option<f64> {
+      let Some(&(plane_id, runway)) = sequence.get(arrivals.len()) else {
+        return Some(problem.landing_cost(arrivals));
+      };
+      let mut best: Option<f64> = None;
+      for time in problem.landing_window(plane_id) {
+        let b = Arrival::on_runway(plane_id, time, runway);
+        let separated = arrivals.iter().all(|a| {
+          !problem.related(a, &b) || a.landing_time + problem.separation_between(a, &b) <= time
+        });
+        if separated {
+          arrivals.push(b);
+          if let Some(cost) = search(problem, sequence, arrivals) {
+            best = Some(best.map_or(cost, |best| best.min(cost)));
+          }
+          arrivals.pop();
+        }
+      }
+      best
+    }
+    search(problem, sequence, &mut Vec::new())
+  }
+
+  #[test]
+  fn timing_matches_brute_force() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let (mut feasible, mut infeasible) = (0, 0);
+    for _ in 0..500 {
+      let problem = random_problem(&mut rng);
+      let mut order: Vec<ID> = (0..problem.planes.len()).collect();
+      order.shuffle(&mut rng);
+      let sequence: Vec<(ID, Runway)> = order
+        .iter()
+        .map(|&plane_id| (plane_id, rng.gen_range(0..problem.runways)))
+        .collect();
+      match (
+        problem.timing_on_runways(&sequence),
+        brute_force(&problem, &sequence),
+      ) {
+        (Ok(solution), Some(cost)) => {
+          feasible += 1;
+          assert_eq!(solution.len(), sequence.len());
+          for (i, &(a_id, a_runway)) in sequence.iter().enumerate() {
+            let a = solution.iter().find(|a| a.plane_id == a_id).unwrap();
+            assert_eq!(a.runway, a_runway);
+            assert!(problem.landing_window(a_id).contains(&a.landing_time));
+            for &(b_id, _) in &sequence[i + 1..] {
+              let b = solution.iter().find(|b| b.plane_id == b_id).unwrap();
+              assert!(
+                !problem.related(a, b)
+                  || a.landing_time + problem.separation_between(a, b) <= b.landing_time
+              );
+            }
+          }
+          assert_eq!(problem.landing_cost(&solution), cost, "{:?}", sequence);
+        }
+        (Err(e), None) => {
+          infeasible += 1;
+          assert!(e.earliest > *problem.landing_window(e.plane_id).end());
+        }
+        (result, cost) => panic!("{:?} but brute force gives {:?}", result, cost),
+      }
+    }
+    assert!(feasible > 100 && infeasible > 100);
+  }
+
+  #[test]
+  fn infeasible_order() {
+    // The second plane cannot land before 5, after its latest landing time
+    let problem = problem(
+      vec![plane(0, 0, 4, (1.0, 2.0)), plane(0, 1, 1, (1.0, 1.0))],
+      vec![vec![99999, 5], vec![3, 99999]],
+    );
+    assert_eq!(
+      problem.timing_on_runways(&[(0, 0), (1, 0)]),
+      Err(InfeasibleOrder {
+        plane_id: 1,
+        earliest: 5
+      })
+    );
+    assert_eq!(
+      problem.timing(&[0, 1]),
+      Err(InfeasibleOrder {
+        plane_id: 1,
+        earliest: 5
+      })
+    );
+    // 4 units late, 4 units after its latest landing time
+    assert_eq!(
+      problem.penalized_cost(&[0, 1]),
+      4.0 + 4.0 * CONFLICT_PENALTY
+    );
+    // In the other order, the second plane lands early so that the first one
+    // is less late
+    let solution = problem.timing(&[1, 0]).unwrap();
+    assert_eq!(solution, vec![Arrival::new(1, 0), Arrival::new(0, 3)]);
+    assert_eq!(problem.landing_cost(&solution), 7.0);
+    let problem = problem.with_runways(2);
+    let solution = problem.timing(&[0, 1]).unwrap();
+    assert_eq!(problem.landing_cost(&solution), 0.0);
+  }
+}
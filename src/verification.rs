@@ -0,0 +1,423 @@
+use crate::problem::{Arrival, LandingProblem, Runway, Solution, ID};
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::{error, fmt};
+
+/// A landing read from a schedule file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+  /// 1-based line of the file
+  pub line: usize,
+  pub plane_id: ID,
+  pub landing_time: u32,
+  pub runway: Runway,
+}
+
+/// Reads a schedule: a landing per line, as a plane id, a whole landing time
+/// and optionally a runway, separated by whitespace or commas. Plane ids
+/// start at `first_id`, runways at 0. Blank lines and lines starting with `#`
+/// are skipped. A first line of column names is a CSV header, whose `id`,
+/// `landing_time` and `runway` columns are read, as written by
+/// `SolutionReport::write_csv`
+pub fn read_schedule(reader: impl BufRead, first_id: ID) -> Result<Vec<Entry>, ScheduleError> {
+  let mut entries = Vec::new();
+  let mut columns = None;
+  for (k, line) in reader.lines().enumerate() {
+    let line = line?;
+    let row = line.trim();
+    if row.is_empty() || row.starts_with('#') {
+      continue;
+    }
+    let fields = split_fields(row);
+    let format_error = || ScheduleError::Format {
+      line: k + 1,
+      row: line.clone(),
+    };
+    if entries.is_empty() && columns.is_none() && fields[0].parse::<f64>().is_err() {
+      let position = |name: &str| fields.iter().position(|field| field == name);
+      let id = position("id").ok_or_else(format_error)?;
+      let time = position("landing_time").ok_or_else(format_error)?;
+      columns = Some((id, time, position("runway")));
+      continue;
+    }
+    let (id, time, runway) = columns.unwrap_or((0, 1, Some(2)));
+    let field = |column: usize| fields.get(column).map(String::as_str);
+    let plane_id = field(id)
+      .and_then(|id| id.parse::<ID>().ok())
+      .and_then(|id| id.checked_sub(first_id))
+      .ok_or_else(format_error)?;
+    let landing_time = field(time)
+      .and_then(|time| time.parse::<f64>().ok())
+      .filter(|&time| time >= 0.0 && time <= u32::MAX as f64 && time.fract() == 0.0)
+      .ok_or_else(format_error)?;
+    let runway = match runway.and_then(field).filter(|runway| !runway.is_empty()) {
+      Some(runway) => runway.parse().map_err(|_| format_error())?,
+      None => 0,
+    };
+    entries.push(Entry {
+      line: k + 1,
+      plane_id,
+      landing_time: landing_time as u32,
+      runway,
+    });
+  }
+  Ok(entries)
+}
+
+pub fn read_schedule_from(file_path: &str, first_id: ID) -> Result<Vec<Entry>, ScheduleError> {
+  read_schedule(io::BufReader::new(File::open(file_path)?), first_id)
+}
+
+/// Fields of a row, split on commas if it has any and on whitespace
+/// otherwise, with CSV quotes removed
+fn split_fields(row: &str) -> Vec<String> {
+  if !row.contains(',') {
+    return row.split_whitespace().map(str::to_string).collect();
+  }
+  let mut fields = vec![String::new()];
+  let mut quoted = false;
+  let mut chars = row.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if quoted && chars.peek() == Some(&'"') => {
+        chars.next();
+        fields.last_mut().unwrap().push('"');
+      }
+      '"' => quoted = !quoted,
+      ',' if !quoted => fields.push(String::new()),
+      c => fields.last_mut().unwrap().push(c),
+    }
+  }
+  fields
+    .iter()
+    .map(|field| field.trim().to_string())
+    .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+  /// A plane id the instance does not have
+  UnknownPlane {
+    line: usize,
+    plane_id: ID,
+  },
+  /// A plane landing again, after the landing kept on `first_line`
+  DuplicatePlane {
+    line: usize,
+    first_line: usize,
+    plane_id: ID,
+  },
+  MissingPlane {
+    plane_id: ID,
+  },
+  UnknownRunway {
+    line: usize,
+    runway: Runway,
+  },
+  /// Landing outside of the landing window
+  Window {
+    plane_id: ID,
+    landing_time: u32,
+    earliest: u32,
+    latest: u32,
+  },
+  /// `second` landing less than the separation time after `first`
+  Separation {
+    first: Arrival,
+    second: Arrival,
+    needed: u32,
+  },
+}
+
+impl fmt::Display for Violation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Violation::UnknownPlane { line, plane_id } => {
+        write!(f, "line {}: unknown plane #{}", line, plane_id)
+      }
+      Violation::DuplicatePlane {
+        line,
+        first_line,
+        plane_id,
+      } => write!(
+        f,
+        "line {}: plane #{} already lands on line {}",
+        line, plane_id, first_line
+      ),
+      Violation::MissingPlane { plane_id } => write!(f, "plane #{} does not land", plane_id),
+      Violation::UnknownRunway { line, runway } => {
+        write!(f, "line {}: unknown runway {}", line, runway)
+      }
+      Violation::Window {
+        plane_id,
+        landing_time,
+        earliest,
+        latest,
+      } => write!(
+        f,
+        "plane #{} lands at {}, outside of [{}, {}]",
+        plane_id, landing_time, earliest, latest
+      ),
+      Violation::Separation {
+        first,
+        second,
+        needed,
+      } => write!(
+        f,
+        "plane #{} lands {} after #{}, {} needed",
+        second.plane_id,
+        second.landing_time - first.landing_time,
+        first.plane_id,
+        needed
+      ),
+    }
+  }
+}
+
+/// A schedule read from a file, as a solution of an instance
+#[derive(Debug, Clone)]
+pub struct Verification {
+  /// The landings of known planes on known runways, the first one of each
+  /// plane, by landing time
+  pub solution: Solution,
+  pub violations: Vec<Violation>,
+}
+
+impl Verification {
+  pub fn is_feasible(&self) -> bool {
+    self.violations.is_empty()
+  }
+}
+
+/// Checks `entries` against `problem`: every plane must land exactly once,
+/// on one of its runways, within its landing window, and separated from
+/// every plane landing before it, not only the previous one
+pub fn verify(problem: &LandingProblem, entries: &[Entry]) -> Verification {
+  let mut violations = Vec::new();
+  let mut lines: Vec<Option<usize>> = vec![None; problem.planes.len()];
+  let mut solution = Vec::new();
+  for entry in entries {
+    if entry.plane_id >= problem.planes.len() {
+      violations.push(Violation::UnknownPlane {
+        line: entry.line,
+        plane_id: entry.plane_id,
+      });
+    } else if let Some(first_line) = lines[entry.plane_id] {
+      violations.push(Violation::DuplicatePlane {
+        line: entry.line,
+        first_line,
+        plane_id: entry.plane_id,
+      });
+    } else if entry.runway >= problem.runways {
+      violations.push(Violation::UnknownRunway {
+        line: entry.line,
+        runway: entry.runway,
+      });
+    } else {
+      lines[entry.plane_id] = Some(entry.line);
+      solution.push(Arrival::on_runway(
+        entry.plane_id,
+        entry.landing_time,
+        entry.runway,
+      ));
+    }
+  }
+  solution.sort_by_key(|a| a.landing_time);
+
+  violations.extend(
+    problem
+      .scheduled_planes()
+      .filter(|plane| lines[plane.id].is_none())
+      .map(|plane| Violation::MissingPlane { plane_id: plane.id }),
+  );
+  for arrival in &solution {
+    let window = problem.landing_window(arrival.plane_id);
+    if !window.contains(&arrival.landing_time) {
+      violations.push(Violation::Window {
+        plane_id: arrival.plane_id,
+        landing_time: arrival.landing_time,
+        earliest: *window.start(),
+        latest: *window.end(),
+      });
+    }
+  }
+  violations.extend(
+    problem
+      .conflicts(&solution)
+      .map(|(first, second, _)| Violation::Separation {
+        first,
+        second,
+        needed: problem.separation_between(&first, &second),
+      }),
+  );
+  Verification {
+    solution,
+    violations,
+  }
+}
+
+#[derive(Debug)]
+pub enum ScheduleError {
+  Io(io::Error),
+  Format {
+    /// 1-based line of the offending row
+    line: usize,
+    row: String,
+  },
+}
+
+impl fmt::Display for ScheduleError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ScheduleError::Io(e) => write!(f, "I/O error: {}", e),
+      ScheduleError::Format { line, row } => write!(
+        f,
+        "line {}: expected plane time [runway], found {:?}",
+        line, row
+      ),
+    }
+  }
+}
+
+impl error::Error for ScheduleError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      ScheduleError::Io(e) => Some(e),
+      ScheduleError::Format { .. } => None,
+    }
+  }
+}
+
+impl From<io::Error> for ScheduleError {
+  fn from(e: io::Error) -> Self {
+    ScheduleError::Io(e)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::parser::{parse_problem, ParseMode};
+  use crate::report::SolutionReport;
+  use std::time::Duration;
+
+  /// Three planes whose separations do not satisfy the triangle inequality:
+  /// the third one needs 10 after the first, but only 2 after the second
+  const INSTANCE: &str = concat!(
+    "3 10\n",
+    "0 10 15 30 1 1\n99999 5 10\n",
+    "0 10 20 40 1 1\n3 99999 2\n",
+    "0 10 25 50 1 1\n3 3 99999\n",
+  );
+
+  fn problem(runways: usize) -> LandingProblem {
+    let data = parse_problem(INSTANCE.as_bytes(), ParseMode::Strict).unwrap();
+    LandingProblem::from_parser(data).with_runways(runways)
+  }
+
+  fn check(runways: usize, schedule: &str) -> Verification {
+    let entries = read_schedule(schedule.as_bytes(), 1).unwrap();
+    verify(&problem(runways), &entries)
+  }
+
+  #[test]
+  fn feasible_schedule() {
+    let verification = check(1, "# plane time\n1 15\n\n2, 20, 0\n3 30\n");
+    assert!(verification.is_feasible(), "{:?}", verification.violations);
+    assert_eq!(
+      verification.solution,
+      vec![
+        Arrival::new(0, 15),
+        Arrival::new(1, 20),
+        Arrival::new(2, 30)
+      ]
+    );
+  }
+
+  #[test]
+  fn reports_every_violation() {
+    let verification = check(1, "1 15\n5 20\n1 16\n2 20 1\n");
+    assert_eq!(
+      verification.violations,
+      vec![
+        Violation::UnknownPlane {
+          line: 2,
+          plane_id: 4
+        },
+        Violation::DuplicatePlane {
+          line: 3,
+          first_line: 1,
+          plane_id: 0
+        },
+        Violation::UnknownRunway { line: 4, runway: 1 },
+        Violation::MissingPlane { plane_id: 1 },
+        Violation::MissingPlane { plane_id: 2 },
+      ]
+    );
+    let verification = check(1, "1 5\n2 20\n3 45\n");
+    assert_eq!(
+      verification.violations,
+      vec![Violation::Window {
+        plane_id: 0,
+        landing_time: 5,
+        earliest: 10,
+        latest: 30
+      }]
+    );
+  }
+
+  #[test]
+  fn separation_from_every_earlier_plane() {
+    // The third plane is far enough from the second one, not from the first
+    let verification = check(1, "1 15\n2 20\n3 22\n");
+    assert_eq!(
+      verification.violations,
+      vec![Violation::Separation {
+        first: Arrival::new(0, 15),
+        second: Arrival::new(2, 22),
+        needed: 10
+      }]
+    );
+    // Runways are independent
+    assert!(check(2, "1 15\n2 20\n3 22 1\n").is_feasible());
+  }
+
+  #[test]
+  fn rejects_malformed_rows() {
+    for schedule in [
+      "1 15.5\n",
+      "1 -3\n",
+      "0 15\n",
+      "1\n",
+      "1 15 x\n",
+      "plane time\n",
+    ] {
+      assert!(matches!(
+        read_schedule(schedule.as_bytes(), 1),
+        Err(ScheduleError::Format { line: 1, .. })
+      ));
+    }
+  }
+
+  #[test]
+  fn reads_csv_reports() {
+    let problem = problem(2);
+    let solution = vec![
+      Arrival::on_runway(0, 15, 0),
+      Arrival::on_runway(2, 16, 1),
+      Arrival::on_runway(1, 20, 0),
+    ];
+    let report = SolutionReport::new("a, \"b\"", &problem, &solution, Duration::ZERO, 1);
+    let mut csv = Vec::new();
+    SolutionReport::write_csv_header(&mut csv).unwrap();
+    report.write_csv(&mut csv).unwrap();
+    let entries = read_schedule(csv.as_slice(), 0).unwrap();
+    assert_eq!(
+      entries.iter().map(|e| e.line).collect::<Vec<_>>(),
+      vec![2, 3, 4]
+    );
+    let verification = verify(&problem, &entries);
+    assert!(verification.is_feasible());
+    assert_eq!(verification.solution, solution);
+  }
+}